@@ -0,0 +1,316 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Ordered attribute list, and parser for the attribute strings of the
+//! convenience API, e.g.
+//! `fill=red stroke-dasharray="5 3" style="font-family: 'DejaVu Sans'"`.
+
+use std::error::Error;
+use std::fmt;
+use std::slice;
+use std::string::ToString;
+use std::vec::Vec;
+
+/// A list of attributes keeping the order in which they were first inserted,
+/// so the output does not change from one run to another.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Attributes {
+    entries: Vec<(String, String)>,
+    // names of the attributes of the element these were taken from, in their
+    // original order, see `apply_order`
+    order: Vec<String>
+}
+
+impl Attributes {
+    pub fn new() -> Attributes {
+        Attributes {
+            entries: Vec::new(),
+            order: Vec::new()
+        }
+    }
+
+    /// Set the attribute `name`. An existing attribute keeps its position and
+    /// its previous value is returned.
+    pub fn insert<T: ToString>(&mut self, name: &str, value: T) -> Option<String> {
+        let value = value.to_string();
+        for &mut (ref n, ref mut v) in self.entries.iter_mut() {
+            if n.as_slice() == name {
+                return Some(::std::mem::replace(v, value))
+            }
+        }
+        self.entries.push((name.to_string(), value));
+        None
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.iter()
+                    .find(|&&(ref n, _)| n.as_slice() == name)
+                    .map(|&(_, ref v)| v.as_slice())
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        match self.entries.iter().position(|&(ref n, _)| n.as_slice() == name) {
+            Some(i) => Some(self.entries.remove(i).1),
+            None    => None
+        }
+    }
+
+    /// Insert all the attributes of `other`, overriding the existing ones.
+    pub fn extend(&mut self, other: &Attributes) {
+        for &(ref name, ref value) in other.iter() {
+            self.insert(name.as_slice(), value);
+        }
+    }
+
+    /// Remember `names` as the order in which the attributes were originally
+    /// written, e.g. in a parsed file, including those moved to the typed
+    /// fields of an entity.
+    pub fn set_order(&mut self, names: Vec<String>) {
+        self.order = names;
+    }
+
+    /// Sort the attributes in the original order recorded in `other`, those
+    /// it does not know keep their relative order after the others.
+    pub fn apply_order(&mut self, other: &Attributes) {
+        if other.order.is_empty() { return }
+        let position = |name: &str| {
+            other.order.iter().position(|n| n.as_slice() == name).unwrap_or(other.order.len())
+        };
+        self.entries.sort_by(|a, b| position(a.0.as_slice()).cmp(&position(b.0.as_slice())));
+    }
+
+    pub fn iter(&self) -> slice::Iter<(String, String)> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// An error in an attribute string, positioned at the offset (in characters)
+/// where it was detected.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AttribError {
+    pub position: usize,
+    pub desc: String
+}
+
+impl fmt::Display for AttribError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.desc, self.position)
+    }
+}
+
+impl Error for AttribError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+fn error(position: usize, desc: String) -> AttribError {
+    AttribError {
+        position: position,
+        desc: desc
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == ':'
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_digit(10) || c == '-' || c == '.'
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        if self.pos < self.chars.len() { Some(self.chars[self.pos]) } else { None }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() { break }
+            self.pos += 1;
+        }
+    }
+
+    fn name(&mut self) -> Result<String, AttribError> {
+        match self.peek() {
+            Some(c) if is_name_start(c) => {},
+            Some(c)                     => {
+                return Err(error(self.pos, format!("unexpected character `{}`", c)))
+            },
+            None                        => return Err(error(self.pos, "expected a name".to_string()))
+        }
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !is_name_char(c) { break }
+            name.push(c);
+            self.pos += 1;
+        }
+        Ok(name)
+    }
+
+    fn value(&mut self, name: &str) -> Result<String, AttribError> {
+        let mut value = String::new();
+        match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                let start = self.pos;
+                self.pos += 1;
+                loop {
+                    match self.peek() {
+                        Some(c) if c == quote => { self.pos += 1; break },
+                        Some(c)               => { value.push(c); self.pos += 1 },
+                        None                  => {
+                            return Err(error(start, format!("unterminated value for `{}`", name)))
+                        }
+                    }
+                }
+                match self.peek() {
+                    Some(c) if !c.is_whitespace() => {
+                        return Err(error(self.pos, format!("expected whitespace after the \
+                                                            value of `{}`", name)))
+                    },
+                    _                             => {/* end of the attribute */}
+                }
+            },
+            _                                            => {
+                while let Some(c) = self.peek() {
+                    if c.is_whitespace() { break }
+                    if c == '"' || c == '\'' || c == '=' {
+                        return Err(error(self.pos, format!("unexpected `{}` in the value of `{}`, \
+                                                            quote the value", c, name)))
+                    }
+                    value.push(c);
+                    self.pos += 1;
+                }
+                if value.is_empty() {
+                    return Err(error(self.pos, format!("missing value for `{}`", name)))
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    fn attribs(&mut self) -> Result<Attributes, AttribError> {
+        let mut attribs = Attributes::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() { return Ok(attribs) }
+            let start = self.pos;
+            let name = try!(self.name());
+            if self.peek() != Some('=') {
+                return Err(error(self.pos, format!("expected `=` after `{}`", name)))
+            }
+            self.pos += 1;
+            let mut value = try!(self.value(name.as_slice()));
+            if name.as_slice() == "style" {
+                value = try!(normalize_style(value.as_slice()).map_err(|mut e| {
+                    // report the position in the whole attribute string
+                    e.position = e.position + start + name.len() + 2;
+                    e
+                }));
+            }
+            if attribs.contains_key(name.as_slice()) {
+                return Err(error(start, format!("duplicate attribute `{}`", name)))
+            }
+            attribs.insert(name.as_slice(), value);
+        }
+    }
+}
+
+/// Parse an attribute string made of whitespace separated `name=value` pairs.
+/// Values containing whitespace are quoted with `"` or `'`.
+pub fn parse_attribs(attribs: &str) -> Result<Attributes, AttribError> {
+    Parser {
+        chars: attribs.chars().collect(),
+        pos: 0
+    }.attribs()
+}
+
+/// Parse the declarations of a `style` attribute, e.g. `fill: red; stroke: blue`.
+pub fn parse_style(style: &str) -> Result<Vec<(String, String)>, AttribError> {
+    let mut declarations = Vec::new();
+    let chars: Vec<char> = style.chars().collect();
+    let mut start = 0;
+    let mut quote = None;
+    // split on the `;` outside of quotes
+    for i in 0..chars.len() + 1 {
+        let c = if i < chars.len() { Some(chars[i]) } else { None };
+        match (quote, c) {
+            (Some(q), Some(c)) if q == c                            => quote = None,
+            (Some(_), Some(_))                                      => {},
+            (Some(_), None)                                         => {
+                return Err(error(start, "unterminated quoted string".to_string()))
+            },
+            (None, Some(c)) if c == '"' || c == '\''                => quote = Some(c),
+            (None, Some(c)) if c != ';'                             => {},
+            (None, _)                                               => {
+                let declaration: String = chars[start..i].iter().cloned().collect();
+                if !declaration.as_slice().trim().is_empty() {
+                    declarations.push(try!(parse_declaration(declaration.as_slice(), start)));
+                }
+                start = i + 1;
+            }
+        }
+    }
+    Ok(declarations)
+}
+
+fn parse_declaration(declaration: &str, offset: usize) -> Result<(String, String), AttribError> {
+    let colon = match declaration.find(':') {
+        Some(i) => i,
+        None    => {
+            return Err(error(offset, format!("expected `:` in the declaration `{}`",
+                                             declaration.trim())))
+        }
+    };
+    let property = declaration[..colon].trim();
+    let value = declaration[colon + 1..].trim();
+    if property.is_empty() || !property.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        return Err(error(offset, format!("invalid property name `{}`", property)))
+    }
+    if value.is_empty() {
+        return Err(error(offset, format!("missing value for the property `{}`", property)))
+    }
+    Ok((property.to_string(), value.to_string()))
+}
+
+fn normalize_style(style: &str) -> Result<String, AttribError> {
+    let declarations: Vec<String> = try!(parse_style(style)).iter()
+        .map(|&(ref property, ref value)| format!("{}:{}", property, value))
+        .collect();
+    Ok(declarations.connect(";"))
+}
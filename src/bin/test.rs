@@ -19,46 +19,51 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-#![feature(io)]
 #![feature(collections)]
-#![feature(path)]
 
 extern crate svg;
 
-use std::old_io::{BufferedWriter, File, Truncate, ReadWrite};
-use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
 
 use svg::SVG;
+use svg::Container;
 use svg::Transform;
+use svg::Data;
+use svg::Attributes;
+use svg::Length;
 // use svg::{Circle, Rect, RoundedRect};
 
 pub fn main() {
-   let mut output = BufferedWriter::new(File::open_mode(&Path::new("output.svg"), Truncate, ReadWrite).unwrap());
-   let mut image = SVG::new(12, 12);
-   let mut attribs = HashMap::new();
-   let polygon_points: Vec<(i32, i32)> = vec![(350,75),  (379,161), (469,161), (397,215),
-                          (423,301), (350,250), (277,301), (303,215),
-                          (231,161), (321,161)];
-   attribs.insert(String::from_str("fill"), String::from_str("green"));
-   attribs.insert(String::from_str("stroke"), String::from_str("orange"));
-   attribs.insert(String::from_str("stroke-width"), String::from_str("2"));
+   let mut output = BufWriter::new(File::create("output.svg").unwrap());
+   let mut image = SVG::new(Length::cm(12.), Length::cm(12.));
+   let mut attribs = Attributes::new();
+   let polygon_points: Vec<(f64, f64)> = vec![(350.,75.),  (379.,161.), (469.,161.), (397.,215.),
+                          (423.,301.), (350.,250.), (277.,301.), (303.,215.),
+                          (231.,161.), (321.,161.)];
+   attribs.insert("fill", "green");
+   attribs.insert("stroke", "orange");
+   attribs.insert("stroke-width", 2);
 
    let mut t = Transform::new();
-   t.translate(100, 200);
-   t.translate(10, 32);
+   t.translate(100., 200.);
+   t.translate(10.5, 32.25);
    println!("{}", t.get());
 
-   image.view_box(0, 0, 1200, 400);
+   image.view_box(0., 0., 1200., 400.).unwrap();
    image.g_begin(Some("First_Group"), Some(&t), Some(&attribs));
    image.g_begin(Some("First_Group"), Some(&t), Some(&attribs));
    // image.g_transform(t.clone());
    //image.g_rotate(15);
-   image.circle(600, 200, 100, "id=jojo fill=red stroke=blue stroke-width=10");
-   image.rect(700, 200, 200, 200, "fill=red stroke=blue stroke-width=10");
-   image.rounded_rect(800, 600, 200, 200, 60, 30, "fill=red stroke=blue stroke-width=10");
-   image.polygon(&polygon_points, "fill=red stroke=blue stroke-width=10");
+   image.circle(600., 200., 100., "id=jojo fill=red stroke=blue stroke-width=10").unwrap();
+   image.rect(700., 200., 200., 200., "fill=red stroke=blue stroke-width=10").unwrap();
+   image.rounded_rect(800., 600., 200., 200., 60., 30., "fill=red stroke=blue stroke-width=10").unwrap();
+   image.polygon(&polygon_points, "fill=red stroke=blue stroke-width=10").unwrap();
    image.g_end();
    image.g_end();
+   let mut d = Data::new();
+   d.move_to(100., 350.).quadratic_curve_by(150., -300., 300., 0.).close();
+   image.path(&d, "fill=none stroke=black stroke-width=5 stroke-dasharray=\"5 3\"").unwrap();
    image.title("Svg library test Main !");
    image.desc("A simple main test for the rust svg generation library");
 
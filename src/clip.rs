@@ -0,0 +1,152 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Clipping paths and masks, built from the shapes and texts of `Container`.
+
+use std::convert::Into;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::{insert_attribs, insert_transform};
+use container::Container;
+use defs::Units;
+use document::Element;
+use length::Length;
+use transform::Transform;
+use SVGEntity;
+
+/// A `<clipPath>`: only the parts of an entity inside its children are drawn.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClipPath {
+    pub units: Option<Units>,
+    pub transform: Option<Transform>,
+    pub attribs: Attributes,
+    pub children: Vec<Element>
+}
+
+/// A `<mask>`: the luminance of its children gives the opacity of the masked
+/// entity. The mask region defaults to the bounding box of the entity
+/// enlarged by 10% on each side.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mask {
+    pub region: Option<(Length, Length, Length, Length)>,
+    pub units: Option<Units>,
+    pub content_units: Option<Units>,
+    pub attribs: Attributes,
+    pub children: Vec<Element>
+}
+
+impl ClipPath {
+    pub fn new() -> ClipPath {
+        ClipPath {
+            units: None,
+            transform: None,
+            attribs: Attributes::new(),
+            children: Vec::new()
+        }
+    }
+
+    /// Value of `clipPathUnits`, `userSpaceOnUse` by default.
+    pub fn units(&mut self, units: Units) -> &mut ClipPath {
+        self.units = Some(units);
+        self
+    }
+
+    pub fn transform(&mut self, transform: Transform) -> &mut ClipPath {
+        self.transform = Some(transform);
+        self
+    }
+}
+
+impl Mask {
+    pub fn new() -> Mask {
+        Mask {
+            region: None,
+            units: None,
+            content_units: None,
+            attribs: Attributes::new(),
+            children: Vec::new()
+        }
+    }
+
+    /// The `x`, `y`, `width` and `height` of the area the mask applies to,
+    /// e.g. `-10%`, `-10%`, `120%` and `120%`.
+    pub fn region<L: Into<Length>>(&mut self, x: L, y: L, width: L, height: L) -> &mut Mask {
+        self.region = Some((x.into(), y.into(), width.into(), height.into()));
+        self
+    }
+
+    /// Value of `maskUnits`, `objectBoundingBox` by default.
+    pub fn units(&mut self, units: Units) -> &mut Mask {
+        self.units = Some(units);
+        self
+    }
+
+    /// Value of `maskContentUnits`, `userSpaceOnUse` by default.
+    pub fn content_units(&mut self, units: Units) -> &mut Mask {
+        self.content_units = Some(units);
+        self
+    }
+}
+
+impl Container for ClipPath {
+    fn add_element(&mut self, element: Element) {
+        self.children.push(element)
+    }
+}
+
+impl Container for Mask {
+    fn add_element(&mut self, element: Element) {
+        self.children.push(element)
+    }
+}
+
+impl SVGEntity for ClipPath {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("clipPath");
+        if let Some(ref u) = self.units { e.set_attrib("clipPathUnits", u) }
+        insert_transform(&mut e, &self.transform);
+        insert_attribs(&mut e, &self.attribs);
+        for child in self.children.iter() {
+            e.add_child(child.clone());
+        }
+        e
+    }
+}
+
+impl SVGEntity for Mask {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("mask");
+        if let Some((x, y, width, height)) = self.region {
+            e.set_attrib("x", x);
+            e.set_attrib("y", y);
+            e.set_attrib("width", width);
+            e.set_attrib("height", height);
+        }
+        if let Some(ref u) = self.units { e.set_attrib("maskUnits", u) }
+        if let Some(ref u) = self.content_units { e.set_attrib("maskContentUnits", u) }
+        insert_attribs(&mut e, &self.attribs);
+        for child in self.children.iter() {
+            e.add_child(child.clone());
+        }
+        e
+    }
+}
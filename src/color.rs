@@ -0,0 +1,546 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Colors, their textual forms and their conversions between color spaces.
+
+use std::ascii::AsciiExt;
+use std::error::Error;
+use std::fmt;
+use std::num::Float;
+use std::str::FromStr;
+use std::vec::Vec;
+
+use common::number;
+
+/// The 147 color keywords of svg, with their sRGB value.
+pub static NAMED_COLORS: [(&'static str, u8, u8, u8); 147] = [
+    ("aliceblue", 240, 248, 255),
+    ("antiquewhite", 250, 235, 215),
+    ("aqua", 0, 255, 255),
+    ("aquamarine", 127, 255, 212),
+    ("azure", 240, 255, 255),
+    ("beige", 245, 245, 220),
+    ("bisque", 255, 228, 196),
+    ("black", 0, 0, 0),
+    ("blanchedalmond", 255, 235, 205),
+    ("blue", 0, 0, 255),
+    ("blueviolet", 138, 43, 226),
+    ("brown", 165, 42, 42),
+    ("burlywood", 222, 184, 135),
+    ("cadetblue", 95, 158, 160),
+    ("chartreuse", 127, 255, 0),
+    ("chocolate", 210, 105, 30),
+    ("coral", 255, 127, 80),
+    ("cornflowerblue", 100, 149, 237),
+    ("cornsilk", 255, 248, 220),
+    ("crimson", 220, 20, 60),
+    ("cyan", 0, 255, 255),
+    ("darkblue", 0, 0, 139),
+    ("darkcyan", 0, 139, 139),
+    ("darkgoldenrod", 184, 134, 11),
+    ("darkgray", 169, 169, 169),
+    ("darkgreen", 0, 100, 0),
+    ("darkgrey", 169, 169, 169),
+    ("darkkhaki", 189, 183, 107),
+    ("darkmagenta", 139, 0, 139),
+    ("darkolivegreen", 85, 107, 47),
+    ("darkorange", 255, 140, 0),
+    ("darkorchid", 153, 50, 204),
+    ("darkred", 139, 0, 0),
+    ("darksalmon", 233, 150, 122),
+    ("darkseagreen", 143, 188, 143),
+    ("darkslateblue", 72, 61, 139),
+    ("darkslategray", 47, 79, 79),
+    ("darkslategrey", 47, 79, 79),
+    ("darkturquoise", 0, 206, 209),
+    ("darkviolet", 148, 0, 211),
+    ("deeppink", 255, 20, 147),
+    ("deepskyblue", 0, 191, 255),
+    ("dimgray", 105, 105, 105),
+    ("dimgrey", 105, 105, 105),
+    ("dodgerblue", 30, 144, 255),
+    ("firebrick", 178, 34, 34),
+    ("floralwhite", 255, 250, 240),
+    ("forestgreen", 34, 139, 34),
+    ("fuchsia", 255, 0, 255),
+    ("gainsboro", 220, 220, 220),
+    ("ghostwhite", 248, 248, 255),
+    ("gold", 255, 215, 0),
+    ("goldenrod", 218, 165, 32),
+    ("gray", 128, 128, 128),
+    ("grey", 128, 128, 128),
+    ("green", 0, 128, 0),
+    ("greenyellow", 173, 255, 47),
+    ("honeydew", 240, 255, 240),
+    ("hotpink", 255, 105, 180),
+    ("indianred", 205, 92, 92),
+    ("indigo", 75, 0, 130),
+    ("ivory", 255, 255, 240),
+    ("khaki", 240, 230, 140),
+    ("lavender", 230, 230, 250),
+    ("lavenderblush", 255, 240, 245),
+    ("lawngreen", 124, 252, 0),
+    ("lemonchiffon", 255, 250, 205),
+    ("lightblue", 173, 216, 230),
+    ("lightcoral", 240, 128, 128),
+    ("lightcyan", 224, 255, 255),
+    ("lightgoldenrodyellow", 250, 250, 210),
+    ("lightgray", 211, 211, 211),
+    ("lightgreen", 144, 238, 144),
+    ("lightgrey", 211, 211, 211),
+    ("lightpink", 255, 182, 193),
+    ("lightsalmon", 255, 160, 122),
+    ("lightseagreen", 32, 178, 170),
+    ("lightskyblue", 135, 206, 250),
+    ("lightslategray", 119, 136, 153),
+    ("lightslategrey", 119, 136, 153),
+    ("lightsteelblue", 176, 196, 222),
+    ("lightyellow", 255, 255, 224),
+    ("lime", 0, 255, 0),
+    ("limegreen", 50, 205, 50),
+    ("linen", 250, 240, 230),
+    ("magenta", 255, 0, 255),
+    ("maroon", 128, 0, 0),
+    ("mediumaquamarine", 102, 205, 170),
+    ("mediumblue", 0, 0, 205),
+    ("mediumorchid", 186, 85, 211),
+    ("mediumpurple", 147, 112, 219),
+    ("mediumseagreen", 60, 179, 113),
+    ("mediumslateblue", 123, 104, 238),
+    ("mediumspringgreen", 0, 250, 154),
+    ("mediumturquoise", 72, 209, 204),
+    ("mediumvioletred", 199, 21, 133),
+    ("midnightblue", 25, 25, 112),
+    ("mintcream", 245, 255, 250),
+    ("mistyrose", 255, 228, 225),
+    ("moccasin", 255, 228, 181),
+    ("navajowhite", 255, 222, 173),
+    ("navy", 0, 0, 128),
+    ("oldlace", 253, 245, 230),
+    ("olive", 128, 128, 0),
+    ("olivedrab", 107, 142, 35),
+    ("orange", 255, 165, 0),
+    ("orangered", 255, 69, 0),
+    ("orchid", 218, 112, 214),
+    ("palegoldenrod", 238, 232, 170),
+    ("palegreen", 152, 251, 152),
+    ("paleturquoise", 175, 238, 238),
+    ("palevioletred", 219, 112, 147),
+    ("papayawhip", 255, 239, 213),
+    ("peachpuff", 255, 218, 185),
+    ("peru", 205, 133, 63),
+    ("pink", 255, 192, 203),
+    ("plum", 221, 160, 221),
+    ("powderblue", 176, 224, 230),
+    ("purple", 128, 0, 128),
+    ("red", 255, 0, 0),
+    ("rosybrown", 188, 143, 143),
+    ("royalblue", 65, 105, 225),
+    ("saddlebrown", 139, 69, 19),
+    ("salmon", 250, 128, 114),
+    ("sandybrown", 244, 164, 96),
+    ("seagreen", 46, 139, 87),
+    ("seashell", 255, 245, 238),
+    ("sienna", 160, 82, 45),
+    ("silver", 192, 192, 192),
+    ("skyblue", 135, 206, 235),
+    ("slateblue", 106, 90, 205),
+    ("slategray", 112, 128, 144),
+    ("slategrey", 112, 128, 144),
+    ("snow", 255, 250, 250),
+    ("springgreen", 0, 255, 127),
+    ("steelblue", 70, 130, 180),
+    ("tan", 210, 180, 140),
+    ("teal", 0, 128, 128),
+    ("thistle", 216, 191, 216),
+    ("tomato", 255, 99, 71),
+    ("turquoise", 64, 224, 208),
+    ("violet", 238, 130, 238),
+    ("wheat", 245, 222, 179),
+    ("white", 255, 255, 255),
+    ("whitesmoke", 245, 245, 245),
+    ("yellow", 255, 255, 0),
+    ("yellowgreen", 154, 205, 50)
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColorError {
+    pub desc: String
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.desc)
+    }
+}
+
+impl Error for ColorError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+/// An sRGB color with an alpha channel in `[0, 1]`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: f64
+}
+
+/// Hue in degrees, saturation, lightness and alpha in `[0, 1]`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Hsl {
+    pub hue: f64,
+    pub saturation: f64,
+    pub lightness: f64,
+    pub alpha: f64
+}
+
+/// Hue in degrees, saturation, value and alpha in `[0, 1]`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Hsv {
+    pub hue: f64,
+    pub saturation: f64,
+    pub value: f64,
+    pub alpha: f64
+}
+
+/// Linear light RGB, components in `[0, 1]`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LinearRgb {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+    pub alpha: f64
+}
+
+/// The perceptual OKLab space: lightness in `[0, 1]`, `a` (green to red) and
+/// `b` (blue to yellow) roughly in `[-0.4, 0.4]`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Oklab {
+    pub lightness: f64,
+    pub a: f64,
+    pub b: f64,
+    pub alpha: f64
+}
+
+fn clamp(v: f64) -> f64 {
+    if v < 0. { 0. } else if v > 1. { 1. } else { v }
+}
+
+fn to_u8(v: f64) -> u8 {
+    (clamp(v) * 255.).round() as u8
+}
+
+fn to_linear(c: u8) -> f64 {
+    let c = c as f64 / 255.;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn from_linear(c: f64) -> u8 {
+    let c = clamp(c);
+    to_u8(if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1. / 2.4) - 0.055 })
+}
+
+// hue in degrees of the color (r, g, b), with max and min its largest and
+// smallest components
+fn hue(r: f64, g: f64, b: f64, max: f64, min: f64) -> f64 {
+    let delta = max - min;
+    if delta == 0. { return 0. }
+    let h = if max == r {
+        ((g - b) / delta) % 6.
+    } else if max == g {
+        (b - r) / delta + 2.
+    } else {
+        (r - g) / delta + 4.
+    };
+    let h = h * 60.;
+    if h < 0. { h + 360. } else { h }
+}
+
+// (r, g, b) in [0, 1] from a hue, a chroma and the value to add to each
+// component to match the lightness / value
+fn from_hue(hue: f64, chroma: f64, m: f64) -> (f64, f64, f64) {
+    let h = ((hue % 360.) + 360.) % 360. / 60.;
+    let x = chroma * (1. - (h % 2. - 1.).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x)
+    };
+    (r + m, g + m, b + m)
+}
+
+fn error<T>(desc: String) -> Result<T, ColorError> {
+    Err(ColorError { desc: desc })
+}
+
+fn hex_digit(c: char) -> Option<u8> {
+    c.to_digit(16).map(|d| d as u8)
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    let digits: Vec<u8> = hex.chars().filter_map(hex_digit).collect();
+    if digits.len() != hex.chars().count() { return None }
+    match digits.len() {
+        3 | 4 => Some(Color {
+            red: digits[0] * 17,
+            green: digits[1] * 17,
+            blue: digits[2] * 17,
+            alpha: if digits.len() == 4 { digits[3] as f64 * 17. / 255. } else { 1. }
+        }),
+        6 | 8 => Some(Color {
+            red: digits[0] * 16 + digits[1],
+            green: digits[2] * 16 + digits[3],
+            blue: digits[4] * 16 + digits[5],
+            alpha: if digits.len() == 8 { (digits[6] * 16 + digits[7]) as f64 / 255. } else { 1. }
+        }),
+        _     => None
+    }
+}
+
+// a finite number
+fn parse_number(s: &str) -> Option<f64> {
+    s.trim().parse::<f64>().ok().and_then(|v| if v.is_finite() { Some(v) } else { None })
+}
+
+// a percentage `n%` as a fraction of `max`
+fn parse_percent(s: &str, max: f64) -> Option<f64> {
+    let s = s.trim();
+    if !s.ends_with("%") { return None }
+    parse_number(&s[..s.len() - 1]).map(|p| p / 100. * max)
+}
+
+// a number, or a percentage of `max` when followed by `%`
+fn parse_component(s: &str, max: f64) -> Option<f64> {
+    if s.trim().ends_with("%") { parse_percent(s, max) } else { parse_number(s) }
+}
+
+fn parse_function(s: &str) -> Option<Color> {
+    let open = match s.find('(') { Some(i) => i, None => return None };
+    if !s.ends_with(")") { return None }
+    let name = s[..open].trim();
+    let args: Vec<&str> = s[open + 1..s.len() - 1]
+        .split(|c: char| c == ',' || c.is_whitespace() || c == '/')
+        .filter(|a| !a.is_empty())
+        .collect();
+    let alpha = match (name, args.len()) {
+        ("rgb", 3) | ("hsl", 3)   => 1.,
+        ("rgba", 4) | ("hsla", 4) => match parse_component(args[3], 1.) {
+            Some(a) => clamp(a),
+            None    => return None
+        },
+        _                         => return None
+    };
+    if name.starts_with("rgb") {
+        let mut c = [0u8; 3];
+        for i in 0..3 {
+            match parse_component(args[i], 255.) {
+                Some(v) => c[i] = to_u8(v / 255.),
+                None    => return None
+            }
+        }
+        Some(Color { red: c[0], green: c[1], blue: c[2], alpha: alpha })
+    } else {
+        let hue = if args[0].ends_with("deg") { &args[0][..args[0].len() - 3] } else { args[0] };
+        let hue = match parse_number(hue) {
+            Some(h) => h,
+            None    => return None
+        };
+        // saturation and lightness are always percentages (CSS Color 3)
+        match (parse_percent(args[1], 1.), parse_percent(args[2], 1.)) {
+            (Some(s), Some(l)) => Some(Color::from_hsl(&Hsl {
+                hue: hue,
+                saturation: clamp(s),
+                lightness: clamp(l),
+                alpha: alpha
+            })),
+            _                  => None
+        }
+    }
+}
+
+impl Color {
+    pub fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color { red: red, green: green, blue: blue, alpha: 1. }
+    }
+
+    /// A color with an opacity `alpha`, clamped to [0, 1].
+    ///
+    /// Panics if `alpha` is NaN or infinite.
+    pub fn rgba(red: u8, green: u8, blue: u8, alpha: f64) -> Color {
+        assert!(alpha.is_finite(), "color alpha must be finite, got {}", alpha);
+        Color { red: red, green: green, blue: blue, alpha: clamp(alpha) }
+    }
+
+    /// Parse `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`, `hsl()`,
+    /// `hsla()` or one of the svg color keywords.
+    pub fn parse(color: &str) -> Result<Color, ColorError> {
+        let trimmed = color.trim();
+        let lower = trimmed.to_ascii_lowercase();
+        let parsed = if lower.starts_with("#") {
+            parse_hex(&lower[1..])
+        } else if lower.contains_char('(') {
+            parse_function(lower.as_slice())
+        } else {
+            Color::named(lower.as_slice())
+        };
+        match parsed {
+            Some(c) => Ok(c),
+            None    => error(format!("invalid color `{}`", trimmed))
+        }
+    }
+
+    /// The color keyword `name`, case insensitive.
+    pub fn named(name: &str) -> Option<Color> {
+        let name = name.to_ascii_lowercase();
+        NAMED_COLORS.iter()
+                    .find(|&&(n, _, _, _)| n == name.as_slice())
+                    .map(|&(_, r, g, b)| Color::rgb(r, g, b))
+    }
+
+    /// The keyword of this color if it has one.
+    pub fn name(&self) -> Option<&'static str> {
+        if self.alpha != 1. { return None }
+        NAMED_COLORS.iter()
+                    .find(|&&(_, r, g, b)| (r, g, b) == (self.red, self.green, self.blue))
+                    .map(|&(n, _, _, _)| n)
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` for a translucent color.
+    pub fn to_hex(&self) -> String {
+        if self.alpha == 1. {
+            format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.red, self.green, self.blue, to_u8(self.alpha))
+        }
+    }
+
+    pub fn to_hsl(&self) -> Hsl {
+        let (r, g, b) = (self.red as f64 / 255., self.green as f64 / 255., self.blue as f64 / 255.);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.;
+        let saturation = if max == min {
+            0.
+        } else {
+            (max - min) / (1. - (2. * lightness - 1.).abs())
+        };
+        Hsl {
+            hue: hue(r, g, b, max, min),
+            saturation: clamp(saturation),
+            lightness: lightness,
+            alpha: self.alpha
+        }
+    }
+
+    pub fn from_hsl(hsl: &Hsl) -> Color {
+        let chroma = (1. - (2. * hsl.lightness - 1.).abs()) * hsl.saturation;
+        let (r, g, b) = from_hue(hsl.hue, chroma, hsl.lightness - chroma / 2.);
+        Color::rgba(to_u8(r), to_u8(g), to_u8(b), hsl.alpha)
+    }
+
+    pub fn to_hsv(&self) -> Hsv {
+        let (r, g, b) = (self.red as f64 / 255., self.green as f64 / 255., self.blue as f64 / 255.);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        Hsv {
+            hue: hue(r, g, b, max, min),
+            saturation: if max == 0. { 0. } else { (max - min) / max },
+            value: max,
+            alpha: self.alpha
+        }
+    }
+
+    pub fn from_hsv(hsv: &Hsv) -> Color {
+        let chroma = hsv.value * hsv.saturation;
+        let (r, g, b) = from_hue(hsv.hue, chroma, hsv.value - chroma);
+        Color::rgba(to_u8(r), to_u8(g), to_u8(b), hsv.alpha)
+    }
+
+    pub fn to_linear(&self) -> LinearRgb {
+        LinearRgb {
+            red: to_linear(self.red),
+            green: to_linear(self.green),
+            blue: to_linear(self.blue),
+            alpha: self.alpha
+        }
+    }
+
+    pub fn from_linear(linear: &LinearRgb) -> Color {
+        Color::rgba(from_linear(linear.red),
+                    from_linear(linear.green),
+                    from_linear(linear.blue),
+                    linear.alpha)
+    }
+
+    pub fn to_oklab(&self) -> Oklab {
+        let c = self.to_linear();
+        let l = (0.4122214708 * c.red + 0.5363325363 * c.green + 0.0514459929 * c.blue).cbrt();
+        let m = (0.2119034982 * c.red + 0.6806995451 * c.green + 0.1073969566 * c.blue).cbrt();
+        let s = (0.0883024619 * c.red + 0.2817188376 * c.green + 0.6299787005 * c.blue).cbrt();
+        Oklab {
+            lightness: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+            alpha: self.alpha
+        }
+    }
+
+    /// The sRGB color closest to `oklab`, colors outside of the sRGB gamut are
+    /// clamped.
+    pub fn from_oklab(oklab: &Oklab) -> Color {
+        let l = (oklab.lightness + 0.3963377774 * oklab.a + 0.2158037573 * oklab.b).powi(3);
+        let m = (oklab.lightness - 0.1055613458 * oklab.a - 0.0638541728 * oklab.b).powi(3);
+        let s = (oklab.lightness - 0.0894841775 * oklab.a - 1.2914855480 * oklab.b).powi(3);
+        Color::from_linear(&LinearRgb {
+            red: 4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            green: -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            blue: -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+            alpha: oklab.alpha
+        })
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Color, ColorError> {
+        Color::parse(s)
+    }
+}
+
+/// `#rrggbb` for opaque colors and `rgba()` otherwise, as `#rrggbbaa` is not
+/// understood by every svg renderer.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.alpha == 1. {
+            write!(f, "{}", self.to_hex())
+        } else {
+            write!(f, "rgba({}, {}, {}, {})", self.red, self.green, self.blue, number(self.alpha))
+        }
+    }
+}
@@ -19,47 +19,126 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use std::collections::HashMap;
-use std::num::Int;
+use std::num::Float;
 
+use attribs::Attributes;
+use color::Color;
+use document::Element;
 use transform::Transform;
 
-pub fn insert_attribs(mut o: String, attribs: &HashMap<String, String>) -> String {
-    for (at, value) in attribs.iter() {
-        o.push_str(format!(" {:?}=\"{:?}\"", *at, *value).as_slice())
+/// Number of digits written for the numbers of the output.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Precision {
+    /// every digit needed to read the number back
+    Full,
+    /// at most this many decimal places
+    Decimals(usize),
+    /// at most this many significant digits, whatever the magnitude
+    Significant(usize)
+}
+
+/// How the numbers of the output are written.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NumberFormat {
+    pub precision: Precision,
+    /// drop the leading zero of fractional numbers, e.g. `.5` for `0.5`
+    pub short: bool
+}
+
+impl NumberFormat {
+    /// Every digit, with the leading zero.
+    pub fn new() -> NumberFormat {
+        NumberFormat {
+            precision: Precision::Full,
+            short: false
+        }
+    }
+
+    pub fn precision(&mut self, precision: Precision) -> &mut NumberFormat {
+        self.precision = precision;
+        self
+    }
+
+    pub fn short(&mut self, short: bool) -> &mut NumberFormat {
+        self.short = short;
+        self
     }
-    o
 }
 
-pub fn insert_transform(mut o: String, transform: &Option<Transform>) -> String {
-    match *transform {
-        Some(ref t) => o.push_str(format!(" {:?}", t.get()).as_slice()),
-        None    => {/* nothing to do */}
+impl Default for NumberFormat {
+    fn default() -> NumberFormat {
+        NumberFormat::new()
     }
-    o
 }
 
-pub fn finalize(mut o: String) -> String{ o.push_str(" />\n"); o }
+/// Format a number for the output: integral values without a fractional part
+/// and no negative zero. The numbers are kept with every digit in the
+/// document, they are only rounded when the image is written.
+pub fn number(value: f64) -> String {
+    format_number(value, &NumberFormat::new())
+}
 
-fn or_max<T: Int + Ord>(int: T, max: T) -> T {
-    if int < max { int } else { max }
+/// Format a number rounded to the precision of `format`, without trailing
+/// zeros and without negative zero.
+///
+/// Panics if `value` is NaN or infinite: svg has no syntax for them.
+pub fn format_number(value: f64, format: &NumberFormat) -> String {
+    assert!(value.is_finite(), "cannot write the non-finite number {}", value);
+    let decimals = match format.precision {
+        Precision::Full                          => None,
+        Precision::Decimals(d)                   => Some(d as isize),
+        Precision::Significant(_) if value == 0. => None,
+        Precision::Significant(d)                => {
+            let magnitude = value.abs().log10().floor() as isize;
+            Some(::std::cmp::max(d, 1) as isize - 1 - magnitude)
+        }
+    };
+    let mut s = match decimals {
+        None               => format!("{}", value),
+        Some(d) if d >= 0  => format!("{:.*}", d as usize, value),
+        Some(d)            => {
+            let unit = 10f64.powi(-d as i32);
+            format!("{}", (value / unit).round() * unit)
+        }
+    };
+    if s.contains_char('.') {
+        let trimmed = s.trim_right_matches('0').trim_right_matches('.').len();
+        s.truncate(trimmed);
+    }
+    if s.as_slice() == "-0" {
+        s = "0".to_string();
+    }
+    if format.short {
+        if s.starts_with("0.") {
+            s.remove(0);
+        } else if s.starts_with("-0.") {
+            s.remove(1);
+        }
+    }
+    s
+}
+
+pub fn insert_attribs(element: &mut Element, attribs: &Attributes) {
+    element.attribs.extend(attribs);
+    element.attribs.apply_order(attribs)
+}
+
+pub fn insert_transform(element: &mut Element, transform: &Option<Transform>) {
+    match *transform {
+        Some(ref t) if !t.is_empty() => element.set_attrib("transform", t.value()),
+        _                            => {/* nothing to do */}
+    }
 }
 
 pub fn rgb(red: u8,
            green: u8,
            blue: u8) -> String {
-    format!("rgb({:?}, {:?}, {:?})", or_max(red, 255),
-                               or_max(green, 255),
-                               or_max(blue, 255))
+    format!("{}", Color::rgb(red, green, blue))
 }
 
 pub fn rgba(red: u8,
             green: u8,
             blue: u8,
             alpha: f32) -> String {
-    format!("rgba({:?}, {:?}, {:?}, {:?})",
-            or_max(red, 255),
-            or_max(green, 255),
-            or_max(blue, 255),
-            if alpha < 1.0 { alpha } else { 1.0 })
+    format!("{}", Color::rgba(red, green, blue, alpha as f64))
 }
@@ -0,0 +1,223 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! The shape API shared by everything entities can be added to: the image
+//! itself and the definitions holding content, such as patterns.
+
+use std::convert::Into;
+use std::vec::Vec;
+
+use attribs::{AttribError, parse_attribs};
+use defs::Handle;
+use document::Element;
+use image::Image;
+use length::Length;
+use marker::Markers;
+use path::{Path, Data};
+use shapes::{Circle, Rect, RoundedRect, Ellipse, Line, PolyLine, Polygon};
+use style::Style;
+use symbol::Use;
+use text::Text;
+use SVGEntity;
+
+pub trait Container {
+    fn add_element(&mut self, element: Element);
+
+    fn add<T: SVGEntity>(&mut self, new_entity: &T) {
+        self.add_element(new_entity.to_element())
+    }
+
+    fn circle<L: Into<Length>>(&mut self,
+                               x: f64,
+                               y: f64,
+                               radius: L,
+              attribs: &str) -> Result<(), AttribError> {
+        self.add(&Circle {
+            x: x,
+            y: y,
+            radius: radius.into(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None
+        });
+        Ok(())
+    }
+
+    fn rect<L: Into<Length>>(&mut self,
+                             x: f64,
+                             y: f64,
+                             width: L,
+                             height: L,
+            attribs: &str) -> Result<(), AttribError> {
+        self.add(&Rect {
+            x: x,
+            y: y,
+            width: width.into(),
+            height: height.into(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None
+        });
+        Ok(())
+    }
+
+    fn rounded_rect<L: Into<Length>>(&mut self,
+                                     x: f64,
+                                     y: f64,
+                                     width: L,
+                                     height: L,
+                                     x_round: L,
+                                     y_round: L,
+                    attribs: &str) -> Result<(), AttribError> {
+        self.add(&RoundedRect {
+            x: x,
+            y: y,
+            width: width.into(),
+            height: height.into(),
+            x_round: x_round.into(),
+            y_round: y_round.into(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None
+        });
+        Ok(())
+    }
+
+    fn ellipse<L: Into<Length>>(&mut self,
+                                x: f64,
+                                y: f64,
+                                x_radius: L,
+                                y_radius: L,
+               attribs: &str) -> Result<(), AttribError> {
+        self.add(&Ellipse {
+            x: x,
+            y: y,
+            x_radius: x_radius.into(),
+            y_radius: y_radius.into(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None
+        });
+        Ok(())
+    }
+
+    fn line(&mut self,
+            x1: f64,
+            y1: f64,
+            x2: f64,
+            y2: f64,
+            attribs: &str) -> Result<(), AttribError> {
+        self.add(&Line {
+            x1: x1,
+            y1: y1,
+            x2: x2,
+            y2: y2,
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None,
+            markers: Markers::new()
+        });
+        Ok(())
+    }
+
+    fn polyline(&mut self,
+                points: &Vec<(f64, f64)>,
+                attribs: &str) -> Result<(), AttribError> {
+        self.add(&PolyLine {
+            points: points.clone(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None,
+            markers: Markers::new()
+        });
+        Ok(())
+    }
+
+    fn polygon(&mut self,
+               points: &Vec<(f64, f64)>,
+               attribs: &str) -> Result<(), AttribError> {
+        self.add(&Polygon {
+            points: points.clone(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None,
+            markers: Markers::new()
+        });
+        Ok(())
+    }
+
+    fn path(&mut self,
+            data: &Data,
+            attribs: &str) -> Result<(), AttribError> {
+        self.add(&Path {
+            data: data.clone(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None,
+            markers: Markers::new()
+        });
+        Ok(())
+    }
+
+    fn text(&mut self,
+            x: f64,
+            y: f64,
+            text: &str,
+            attribs: &str) -> Result<(), AttribError> {
+        self.add(&Text {
+            x: x,
+            y: y,
+            text: text.to_string(),
+            style: Style::new(),
+            attribs: try!(parse_attribs(attribs)),
+            transform: None
+        });
+        Ok(())
+    }
+
+    /// Draw the external image `href`.
+    fn image<L: Into<Length>>(&mut self,
+                              x: f64,
+                              y: f64,
+                              width: L,
+                              height: L,
+             href: &str,
+             attribs: &str) -> Result<(), AttribError> {
+        let mut image = Image::new(x, y, width, height, href);
+        image.attribs = try!(parse_attribs(attribs));
+        self.add(&image);
+        Ok(())
+    }
+
+    /// Draw an instance of the `Symbol` registered as `symbol`, scaled to fit
+    /// `width` by `height` at (`x`, `y`).
+    fn use_symbol<L: Into<Length>>(&mut self,
+                                   symbol: &Handle,
+                                   x: f64,
+                                   y: f64,
+                                   width: L,
+                                   height: L) {
+        let mut u = Use::new(symbol, x, y);
+        u.width = Some(width.into());
+        u.height = Some(height.into());
+        self.add(&u)
+    }
+}
@@ -0,0 +1,186 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! The `<defs>` section of a document: paint servers and other resources
+//! which are referenced by id instead of being rendered directly.
+
+use std::error::Error;
+use std::fmt;
+use std::vec::Vec;
+
+use document::Element;
+use SVGEntity;
+
+/// Reference to a definition, obtained when it is registered with
+/// `SVG::define`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Handle {
+    id: String
+}
+
+/// Coordinate system of the attributes of a definition, i.e. the value of
+/// `gradientUnits`, `patternUnits`, `clipPathUnits`, ...
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Units {
+    UserSpaceOnUse,
+    ObjectBoundingBox
+}
+
+/// The definitions of a document, written in a single `<defs>` element at the
+/// top of the image.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Defs {
+    pub children: Vec<Element>
+}
+
+/// A definition without id, or with the id of another element.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DefsError {
+    pub desc: String
+}
+
+impl fmt::Display for DefsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.desc)
+    }
+}
+
+impl Error for DefsError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+/// The ids generated for the definitions added without one, made of the
+/// element name and a number. The owner of the image checks that a candidate
+/// is not used by any of its elements before taking it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct IdGenerator {
+    last_id: usize
+}
+
+impl IdGenerator {
+    pub fn new() -> IdGenerator {
+        IdGenerator {
+            last_id: 0
+        }
+    }
+
+    /// The next candidate id starting with `prefix`.
+    pub fn next(&mut self, prefix: &str) -> String {
+        self.last_id += 1;
+        format!("{}{}", prefix, self.last_id)
+    }
+}
+
+impl Handle {
+    /// A handle to a definition with a known id, e.g. from a parsed file.
+    pub fn new(id: &str) -> Handle {
+        Handle {
+            id: id.to_string()
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.id.as_slice()
+    }
+
+    /// `url(#id)`, the value of paint and reference attributes.
+    pub fn url(&self) -> String {
+        format!("url(#{})", self.id)
+    }
+
+    /// `#id`, the value of `xlink:href`.
+    pub fn href(&self) -> String {
+        format!("#{}", self.id)
+    }
+}
+
+impl Defs {
+    pub fn new() -> Defs {
+        Defs {
+            children: Vec::new()
+        }
+    }
+
+    /// Add `element`, whose id must not be used by another definition.
+    /// `SVG::define` generates an id for the elements which have none.
+    pub fn add(&mut self, element: Element) -> Result<Handle, DefsError> {
+        let handle = match element.id() {
+            Some(id) if self.find(id).is_some() => {
+                return Err(DefsError { desc: format!("duplicate id `{}`", id) })
+            },
+            Some(id)                            => Handle::new(id),
+            None                                => {
+                return Err(DefsError { desc: format!("a <{}> definition has no id",
+                                                     element.name) })
+            }
+        };
+        self.children.push(element);
+        Ok(handle)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Element> {
+        self.children.iter().filter_map(|e| e.find(id)).next()
+    }
+
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut Element> {
+        for e in self.children.iter_mut() {
+            if let Some(found) = e.find_mut(id) { return Some(found) }
+        }
+        None
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Element> {
+        match self.children.iter().position(|e| e.id() == Some(id)) {
+            Some(i) => Some(self.children.remove(i)),
+            None    => {
+                for e in self.children.iter_mut() {
+                    if let Some(removed) = e.remove(id) { return Some(removed) }
+                }
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl SVGEntity for Defs {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("defs");
+        for child in self.children.iter() {
+            e.add_child(child.clone());
+        }
+        e
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            Units::UserSpaceOnUse    => "userSpaceOnUse",
+            Units::ObjectBoundingBox => "objectBoundingBox"
+        })
+    }
+}
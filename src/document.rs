@@ -0,0 +1,213 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! In-memory representation of an svg document.
+//!
+//! Every entity added to an `SVG` is converted into an `Element` and kept in a
+//! `Document` until the image is finalized, so it can still be found, modified,
+//! moved or removed after being added.
+
+use std::fmt;
+use std::string::ToString;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use entity::Entity;
+use xml::Serializer;
+use SVGEntity;
+
+/// A node of the document tree.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Node {
+    Element(Element),
+    Text(String)
+}
+
+/// A generic svg element: a tag name, its attributes and its children.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Element {
+    pub name: String,
+    pub attribs: Attributes,
+    pub children: Vec<Node>
+}
+
+/// The body of an svg image, i.e. everything between the head and the closing
+/// `</svg>` tag.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Document {
+    pub children: Vec<Node>
+}
+
+fn find<'a>(nodes: &'a Vec<Node>, id: &str) -> Option<&'a Element> {
+    for node in nodes.iter() {
+        if let Node::Element(ref e) = *node {
+            if let Some(found) = e.find(id) { return Some(found) }
+        }
+    }
+    None
+}
+
+fn find_mut<'a>(nodes: &'a mut Vec<Node>, id: &str) -> Option<&'a mut Element> {
+    for node in nodes.iter_mut() {
+        if let Node::Element(ref mut e) = *node {
+            if let Some(found) = e.find_mut(id) { return Some(found) }
+        }
+    }
+    None
+}
+
+fn remove(nodes: &mut Vec<Node>, id: &str) -> Option<Element> {
+    let pos = nodes.iter().position(|n| match *n {
+        Node::Element(ref e) => e.id() == Some(id),
+        Node::Text(_)        => false
+    });
+    match pos {
+        Some(i) => match nodes.remove(i) {
+            Node::Element(e) => Some(e),
+            Node::Text(_)    => unreachable!()
+        },
+        None    => {
+            for node in nodes.iter_mut() {
+                if let Node::Element(ref mut e) = *node {
+                    if let Some(removed) = remove(&mut e.children, id) {
+                        return Some(removed)
+                    }
+                }
+            }
+            None
+        }
+    }
+}
+
+impl Element {
+    pub fn new(name: &str) -> Element {
+        Element {
+            name: name.to_string(),
+            attribs: Attributes::new(),
+            children: Vec::new()
+        }
+    }
+
+    /// Set the attribute `name`, replacing its value if it already exists.
+    pub fn set_attrib<T: ToString>(&mut self, name: &str, value: T) {
+        self.attribs.insert(name, value);
+    }
+
+    pub fn get_attrib(&self, name: &str) -> Option<&str> {
+        self.attribs.get(name)
+    }
+
+    pub fn remove_attrib(&mut self, name: &str) -> Option<String> {
+        self.attribs.remove(name)
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.get_attrib("id")
+    }
+
+    pub fn add_child(&mut self, child: Element) {
+        self.children.push(Node::Element(child))
+    }
+
+    pub fn add_text(&mut self, text: &str) {
+        self.children.push(Node::Text(text.to_string()))
+    }
+
+    /// Search this element and its descendants for the element with the given id.
+    pub fn find(&self, id: &str) -> Option<&Element> {
+        if self.id() == Some(id) { return Some(self) }
+        find(&self.children, id)
+    }
+
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut Element> {
+        if self.id() == Some(id) { return Some(self) }
+        find_mut(&mut self.children, id)
+    }
+
+    /// Detach the descendant with the given id and return it.
+    pub fn remove(&mut self, id: &str) -> Option<Element> {
+        remove(&mut self.children, id)
+    }
+}
+
+impl SVGEntity for Element {
+    fn to_element(&self) -> Element {
+        self.clone()
+    }
+}
+
+impl Document {
+    pub fn new() -> Document {
+        Document {
+            children: Vec::new()
+        }
+    }
+
+    pub fn add(&mut self, element: Element) {
+        self.children.push(Node::Element(element))
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Element> {
+        find(&self.children, id)
+    }
+
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut Element> {
+        find_mut(&mut self.children, id)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Element> {
+        remove(&mut self.children, id)
+    }
+
+    /// Typed view of the top level elements of the document.
+    pub fn entities(&self) -> Vec<Entity> {
+        self.children.iter().filter_map(|n| match *n {
+            Node::Element(ref e) => Some(Entity::from_element(e)),
+            Node::Text(_)        => None
+        }).collect()
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = Serializer::new();
+        s.node(self);
+        write!(f, "{}", s.as_slice())
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = Serializer::new();
+        s.element(self);
+        write!(f, "{}", s.as_slice())
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = Serializer::new();
+        for child in self.children.iter() {
+            s.node(child);
+        }
+        write!(f, "{}", s.as_slice())
+    }
+}
@@ -0,0 +1,315 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Typed view over the elements of a document.
+
+use std::str::FromStr;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::insert_attribs;
+use defs::Handle;
+use document::{Element, Node};
+use length::Length;
+use marker::Markers;
+use path::{Path, Data};
+use shapes::{Circle, Rect, RoundedRect, Ellipse, Line, PolyLine, Polygon};
+use style::Style;
+use symbol::Use;
+use text::Text;
+use SVGEntity;
+
+macro_rules! try_opt {
+    ($e:expr) => (match $e { Some(v) => v, None => return None })
+}
+
+/// A `<g>` element and its children.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Group {
+    pub style: Style,
+    pub attribs: Attributes,
+    pub children: Vec<Entity>
+}
+
+/// One of the entities known by the library, or a generic element for
+/// everything else.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Entity {
+    Circle(Circle),
+    Ellipse(Ellipse),
+    Line(Line),
+    PolyLine(PolyLine),
+    Polygon(Polygon),
+    Path(Path),
+    Rect(Rect),
+    RoundedRect(RoundedRect),
+    Text(Text),
+    Group(Group),
+    Use(Use),
+    Other(Element)
+}
+
+// Remove the attribute `name` and parse it, a missing attribute takes the
+// default value of the svg specification.
+fn take<T: FromStr>(attribs: &mut Attributes,
+                    name: &str,
+                    default: T) -> Option<T> {
+    match attribs.remove(name) {
+        Some(v) => v.as_slice().trim().parse::<T>().ok(),
+        None    => Some(default)
+    }
+}
+
+fn take_points(attribs: &mut Attributes) -> Option<Vec<(f64, f64)>> {
+    let value = match attribs.remove("points") {
+        Some(v) => v,
+        None    => return Some(Vec::new())
+    };
+    let mut numbers = Vec::new();
+    for n in value.as_slice().split(|c: char| c == ',' || c.is_whitespace()) {
+        if n.is_empty() { continue }
+        match n.parse::<f64>() {
+            Ok(n)  => numbers.push(n),
+            Err(_) => return None
+        }
+    }
+    if numbers.len() % 2 != 0 { return None }
+    Some(numbers.chunks(2).map(|p| (p[0], p[1])).collect())
+}
+
+// Remove the attribute `name` if it is a `url(#id)` reference, anything else
+// stays a raw attribute.
+fn take_url(attribs: &mut Attributes, name: &str) -> Option<Handle> {
+    let id = match attribs.get(name) {
+        Some(v) if v.trim().starts_with("url(#") && v.trim().ends_with(")") => {
+            let v = v.trim();
+            v[5..v.len() - 1].trim().to_string()
+        },
+        _                                                                   => return None
+    };
+    attribs.remove(name);
+    Some(Handle::new(id.as_slice()))
+}
+
+fn take_markers(attribs: &mut Attributes) -> Markers {
+    Markers {
+        start: take_url(attribs, "marker-start"),
+        mid: take_url(attribs, "marker-mid"),
+        end: take_url(attribs, "marker-end")
+    }
+}
+
+fn text_content(element: &Element) -> Option<String> {
+    let mut text = String::new();
+    for child in element.children.iter() {
+        match *child {
+            Node::Text(ref t)    => text.push_str(t.as_slice()),
+            Node::Element(_)     => return None
+        }
+    }
+    Some(text)
+}
+
+fn convert(element: &Element) -> Option<Entity> {
+    let mut a = element.attribs.clone();
+    // the typed fields are written back where they were in `element`
+    a.set_order(element.attribs.iter().map(|&(ref name, _)| name.clone()).collect());
+    let entity = match element.name.as_slice() {
+        "circle"    => Entity::Circle(Circle {
+            x: try_opt!(take(&mut a, "cx", 0.)),
+            y: try_opt!(take(&mut a, "cy", 0.)),
+            radius: try_opt!(take(&mut a, "r", Length::user(0.))),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None
+        }),
+        "ellipse"   => Entity::Ellipse(Ellipse {
+            x: try_opt!(take(&mut a, "cx", 0.)),
+            y: try_opt!(take(&mut a, "cy", 0.)),
+            x_radius: try_opt!(take(&mut a, "rx", Length::user(0.))),
+            y_radius: try_opt!(take(&mut a, "ry", Length::user(0.))),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None
+        }),
+        "line"      => Entity::Line(Line {
+            x1: try_opt!(take(&mut a, "x1", 0.)),
+            y1: try_opt!(take(&mut a, "y1", 0.)),
+            x2: try_opt!(take(&mut a, "x2", 0.)),
+            y2: try_opt!(take(&mut a, "y2", 0.)),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None,
+            markers: take_markers(&mut a)
+        }),
+        "polyline"  => Entity::PolyLine(PolyLine {
+            points: try_opt!(take_points(&mut a)),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None,
+            markers: take_markers(&mut a)
+        }),
+        "polygon"   => Entity::Polygon(Polygon {
+            points: try_opt!(take_points(&mut a)),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None,
+            markers: take_markers(&mut a)
+        }),
+        "path"      => Entity::Path(Path {
+            data: try_opt!(take(&mut a, "d", Data::new())),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None,
+            markers: take_markers(&mut a)
+        }),
+        "rect" if a.contains_key("rx") || a.contains_key("ry") => {
+            // a missing radius takes the value of the other one
+            let rx = a.get("rx").or(a.get("ry")).unwrap().to_string();
+            let ry = a.get("ry").or(a.get("rx")).unwrap().to_string();
+            a.insert("rx", rx);
+            a.insert("ry", ry);
+            Entity::RoundedRect(RoundedRect {
+                x: try_opt!(take(&mut a, "x", 0.)),
+                y: try_opt!(take(&mut a, "y", 0.)),
+                width: try_opt!(take(&mut a, "width", Length::user(0.))),
+                height: try_opt!(take(&mut a, "height", Length::user(0.))),
+                x_round: try_opt!(take(&mut a, "rx", Length::user(0.))),
+                y_round: try_opt!(take(&mut a, "ry", Length::user(0.))),
+                style: Style::new(),
+                attribs: Attributes::new(),
+                transform: None
+            })
+        },
+        "rect"      => Entity::Rect(Rect {
+            x: try_opt!(take(&mut a, "x", 0.)),
+            y: try_opt!(take(&mut a, "y", 0.)),
+            width: try_opt!(take(&mut a, "width", Length::user(0.))),
+            height: try_opt!(take(&mut a, "height", Length::user(0.))),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None
+        }),
+        "text"      => Entity::Text(Text {
+            x: try_opt!(take(&mut a, "x", 0.)),
+            y: try_opt!(take(&mut a, "y", 0.)),
+            text: try_opt!(text_content(element)),
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None
+        }),
+        "use"       => {
+            let href = a.remove("xlink:href").or_else(|| a.remove("href"));
+            let id = match href {
+                Some(ref h) if h.as_slice().starts_with("#") => h.as_slice()[1..].to_string(),
+                _                                            => return None
+            };
+            Entity::Use(Use {
+                href: Handle::new(id.as_slice()),
+                x: try_opt!(take(&mut a, "x", 0.)),
+                y: try_opt!(take(&mut a, "y", 0.)),
+                width: match a.remove("width") {
+                    Some(w) => Some(try_opt!(w.as_slice().parse::<Length>().ok())),
+                    None    => None
+                },
+                height: match a.remove("height") {
+                    Some(h) => Some(try_opt!(h.as_slice().parse::<Length>().ok())),
+                    None    => None
+                },
+                style: Style::new(),
+                attribs: Attributes::new(),
+                transform: None
+            })
+        },
+        "g"         => Entity::Group(Group {
+            style: Style::new(),
+            attribs: Attributes::new(),
+            children: element.children.iter().filter_map(|n| match *n {
+                Node::Element(ref e) => Some(Entity::from_element(e)),
+                Node::Text(_)        => None
+            }).collect()
+        }),
+        _           => return None
+    };
+    // whatever was not consumed above stays as a raw attribute
+    Some(entity.with_attribs(a))
+}
+
+impl Entity {
+    /// Convert `element` into the matching entity, falling back to
+    /// `Entity::Other` for unknown elements or elements whose attributes cannot
+    /// be represented by the typed entity.
+    pub fn from_element(element: &Element) -> Entity {
+        match convert(element) {
+            Some(entity) => entity,
+            None         => Entity::Other(element.clone())
+        }
+    }
+
+    fn with_attribs(mut self, attribs: Attributes) -> Entity {
+        match self {
+            Entity::Circle(ref mut e)      => e.attribs = attribs,
+            Entity::Ellipse(ref mut e)     => e.attribs = attribs,
+            Entity::Line(ref mut e)        => e.attribs = attribs,
+            Entity::PolyLine(ref mut e)    => e.attribs = attribs,
+            Entity::Polygon(ref mut e)     => e.attribs = attribs,
+            Entity::Path(ref mut e)        => e.attribs = attribs,
+            Entity::Rect(ref mut e)        => e.attribs = attribs,
+            Entity::RoundedRect(ref mut e) => e.attribs = attribs,
+            Entity::Text(ref mut e)        => e.attribs = attribs,
+            Entity::Group(ref mut e)       => e.attribs = attribs,
+            Entity::Use(ref mut e)         => e.attribs = attribs,
+            Entity::Other(_)               => {/* kept as is */}
+        }
+        self
+    }
+}
+
+impl SVGEntity for Group {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("g");
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        for child in self.children.iter() {
+            e.add_child(child.to_element());
+        }
+        e
+    }
+}
+
+impl SVGEntity for Entity {
+    fn to_element(&self) -> Element {
+        match *self {
+            Entity::Circle(ref e)      => e.to_element(),
+            Entity::Ellipse(ref e)     => e.to_element(),
+            Entity::Line(ref e)        => e.to_element(),
+            Entity::PolyLine(ref e)    => e.to_element(),
+            Entity::Polygon(ref e)     => e.to_element(),
+            Entity::Path(ref e)        => e.to_element(),
+            Entity::Rect(ref e)        => e.to_element(),
+            Entity::RoundedRect(ref e) => e.to_element(),
+            Entity::Text(ref e)        => e.to_element(),
+            Entity::Group(ref e)       => e.to_element(),
+            Entity::Use(ref e)         => e.to_element(),
+            Entity::Other(ref e)       => e.clone()
+        }
+    }
+}
@@ -0,0 +1,596 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Filter effects: `<filter>` and its primitives.
+//!
+//! The wiring of the primitives is checked when they are added, a primitive
+//! can only read the result of a primitive added before it.
+
+use std::convert::Into;
+use std::error::Error;
+use std::fmt;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use color::Color;
+use common::{insert_attribs, number};
+use defs::Units;
+use document::Element;
+use length::Length;
+use SVGEntity;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FilterError {
+    pub desc: String
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.desc)
+    }
+}
+
+impl Error for FilterError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+fn error<T>(desc: String) -> Result<T, FilterError> {
+    Err(FilterError { desc: desc })
+}
+
+/// Value of `in` and `in2`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Input {
+    SourceGraphic,
+    SourceAlpha,
+    BackgroundImage,
+    BackgroundAlpha,
+    FillPaint,
+    StrokePaint,
+    /// the result of a previous primitive
+    Result(String)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CompositeOperator {
+    Over,
+    In,
+    Out,
+    Atop,
+    Xor,
+    /// `k1 * i1 * i2 + k2 * i1 + k3 * i2 + k4`
+    Arithmetic(f64, f64, f64, f64)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Darken,
+    Lighten
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MorphologyOperator {
+    Erode,
+    Dilate
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Channel {
+    R,
+    G,
+    B,
+    A
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ColorMatrix {
+    /// 4 rows of 5 values
+    Matrix(Vec<f64>),
+    Saturate(f64),
+    HueRotate(f64),
+    LuminanceToAlpha
+}
+
+/// Transfer function of one channel of `ComponentTransfer`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TransferFunction {
+    Identity,
+    Table(Vec<f64>),
+    Discrete(Vec<f64>),
+    /// slope and intercept
+    Linear(f64, f64),
+    /// amplitude, exponent and offset
+    Gamma(f64, f64, f64)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LightSource {
+    /// azimuth and elevation in degrees
+    Distant(f64, f64),
+    /// position
+    Point(f64, f64, f64),
+    /// position, point at which the light points, specular exponent and
+    /// limiting cone angle
+    Spot((f64, f64, f64), (f64, f64, f64), f64, Option<f64>)
+}
+
+/// A filter primitive. The inputs left to `None` read the result of the
+/// previous primitive, or the source graphic for the first one.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Primitive {
+    /// input and standard deviation along x and y
+    GaussianBlur(Option<Input>, f64, f64),
+    /// input, dx and dy
+    Offset(Option<Input>, f64, f64),
+    Flood(Color),
+    Composite(Option<Input>, Input, CompositeOperator),
+    Merge(Vec<Input>),
+    ColorMatrix(Option<Input>, ColorMatrix),
+    Blend(Option<Input>, Input, BlendMode),
+    /// input, operator and radius along x and y
+    Morphology(Option<Input>, MorphologyOperator, f64, f64),
+    /// base frequency along x and y, octaves, seed, fractal noise instead of
+    /// turbulence, stitch tiles
+    Turbulence(f64, f64, u32, f64, bool, bool),
+    /// input, displacement map, scale and channels used along x and y
+    DisplacementMap(Option<Input>, Input, f64, Channel, Channel),
+    /// input and the functions of the red, green, blue and alpha channels
+    ComponentTransfer(Option<Input>, TransferFunction, TransferFunction,
+                      TransferFunction, TransferFunction),
+    /// input, dx, dy, standard deviation and shadow color
+    DropShadow(Option<Input>, f64, f64, f64, Color),
+    /// input, surface scale, diffuse constant, light color and source
+    DiffuseLighting(Option<Input>, f64, f64, Color, LightSource),
+    /// input, surface scale, specular constant, specular exponent, light color
+    /// and source
+    SpecularLighting(Option<Input>, f64, f64, f64, Color, LightSource)
+}
+
+/// A `<filter>`, registered with `SVG::define` and applied with
+/// `Style::filter` or `SVG::g_filter`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Filter {
+    pub region: Option<(Length, Length, Length, Length)>,
+    pub units: Option<Units>,
+    pub primitive_units: Option<Units>,
+    pub attribs: Attributes,
+    primitives: Vec<(Primitive, Option<String>)>
+}
+
+impl Primitive {
+    fn inputs(&self) -> Vec<&Input> {
+        let mut inputs = Vec::new();
+        match *self {
+            Primitive::GaussianBlur(ref i, _, _)
+            | Primitive::Offset(ref i, _, _)
+            | Primitive::ColorMatrix(ref i, _)
+            | Primitive::Morphology(ref i, _, _, _)
+            | Primitive::ComponentTransfer(ref i, _, _, _, _)
+            | Primitive::DropShadow(ref i, _, _, _, _)
+            | Primitive::DiffuseLighting(ref i, _, _, _, _)
+            | Primitive::SpecularLighting(ref i, _, _, _, _, _) => inputs.extend(i.iter()),
+            Primitive::Composite(ref i, ref i2, _)
+            | Primitive::Blend(ref i, ref i2, _)
+            | Primitive::DisplacementMap(ref i, ref i2, _, _, _) => {
+                inputs.extend(i.iter());
+                inputs.push(i2);
+            },
+            Primitive::Merge(ref i)                              => inputs.extend(i.iter()),
+            Primitive::Flood(_) | Primitive::Turbulence(..)      => {/* no input */}
+        }
+        inputs
+    }
+
+    fn to_element(&self) -> Element {
+        fn input(e: &mut Element, name: &str, input: &Option<Input>) {
+            if let Some(ref i) = *input { e.set_attrib(name, i) }
+        }
+        fn deviation(x: f64, y: f64) -> String {
+            if x == y { number(x) } else { format!("{} {}", number(x), number(y)) }
+        }
+        fn color(e: &mut Element, name: &str, opacity: &str, c: &Color) {
+            e.set_attrib(name, Color { alpha: 1., ..*c });
+            if c.alpha != 1. { e.set_attrib(opacity, number(c.alpha)) }
+        }
+        let mut e;
+        match *self {
+            Primitive::GaussianBlur(ref i, x, y)                   => {
+                e = Element::new("feGaussianBlur");
+                input(&mut e, "in", i);
+                e.set_attrib("stdDeviation", deviation(x, y));
+            },
+            Primitive::Offset(ref i, dx, dy)                       => {
+                e = Element::new("feOffset");
+                input(&mut e, "in", i);
+                e.set_attrib("dx", number(dx));
+                e.set_attrib("dy", number(dy));
+            },
+            Primitive::Flood(ref c)                                => {
+                e = Element::new("feFlood");
+                color(&mut e, "flood-color", "flood-opacity", c);
+            },
+            Primitive::Composite(ref i, ref i2, ref operator)      => {
+                e = Element::new("feComposite");
+                input(&mut e, "in", i);
+                e.set_attrib("in2", i2);
+                e.set_attrib("operator", operator);
+                if let CompositeOperator::Arithmetic(k1, k2, k3, k4) = *operator {
+                    e.set_attrib("k1", number(k1));
+                    e.set_attrib("k2", number(k2));
+                    e.set_attrib("k3", number(k3));
+                    e.set_attrib("k4", number(k4));
+                }
+            },
+            Primitive::Merge(ref inputs)                           => {
+                e = Element::new("feMerge");
+                for i in inputs.iter() {
+                    let mut node = Element::new("feMergeNode");
+                    node.set_attrib("in", i);
+                    e.add_child(node);
+                }
+            },
+            Primitive::ColorMatrix(ref i, ref matrix)              => {
+                e = Element::new("feColorMatrix");
+                input(&mut e, "in", i);
+                match *matrix {
+                    ColorMatrix::Matrix(ref values) => {
+                        let values: Vec<String> = values.iter().map(|v| number(*v)).collect();
+                        e.set_attrib("type", "matrix");
+                        e.set_attrib("values", values.connect(" "));
+                    },
+                    ColorMatrix::Saturate(s)        => {
+                        e.set_attrib("type", "saturate");
+                        e.set_attrib("values", number(s));
+                    },
+                    ColorMatrix::HueRotate(a)       => {
+                        e.set_attrib("type", "hueRotate");
+                        e.set_attrib("values", number(a));
+                    },
+                    ColorMatrix::LuminanceToAlpha   => e.set_attrib("type", "luminanceToAlpha")
+                }
+            },
+            Primitive::Blend(ref i, ref i2, ref mode)              => {
+                e = Element::new("feBlend");
+                input(&mut e, "in", i);
+                e.set_attrib("in2", i2);
+                e.set_attrib("mode", mode);
+            },
+            Primitive::Morphology(ref i, ref operator, rx, ry)     => {
+                e = Element::new("feMorphology");
+                input(&mut e, "in", i);
+                e.set_attrib("operator", operator);
+                e.set_attrib("radius", deviation(rx, ry));
+            },
+            Primitive::Turbulence(fx, fy, octaves, seed, fractal, stitch) => {
+                e = Element::new("feTurbulence");
+                e.set_attrib("type", if fractal { "fractalNoise" } else { "turbulence" });
+                e.set_attrib("baseFrequency", deviation(fx, fy));
+                e.set_attrib("numOctaves", octaves);
+                e.set_attrib("seed", number(seed));
+                if stitch { e.set_attrib("stitchTiles", "stitch") }
+            },
+            Primitive::DisplacementMap(ref i, ref i2, scale, x, y) => {
+                e = Element::new("feDisplacementMap");
+                input(&mut e, "in", i);
+                e.set_attrib("in2", i2);
+                e.set_attrib("scale", number(scale));
+                e.set_attrib("xChannelSelector", x);
+                e.set_attrib("yChannelSelector", y);
+            },
+            Primitive::ComponentTransfer(ref i, ref r, ref g, ref b, ref a) => {
+                e = Element::new("feComponentTransfer");
+                input(&mut e, "in", i);
+                for &(name, function) in [("feFuncR", r), ("feFuncG", g),
+                                          ("feFuncB", b), ("feFuncA", a)].iter() {
+                    if *function != TransferFunction::Identity {
+                        e.add_child(function.to_element(name));
+                    }
+                }
+            },
+            Primitive::DropShadow(ref i, dx, dy, deviation, ref c) => {
+                e = Element::new("feDropShadow");
+                input(&mut e, "in", i);
+                e.set_attrib("dx", number(dx));
+                e.set_attrib("dy", number(dy));
+                e.set_attrib("stdDeviation", number(deviation));
+                color(&mut e, "flood-color", "flood-opacity", c);
+            },
+            Primitive::DiffuseLighting(ref i, scale, constant, ref c, ref light) => {
+                e = Element::new("feDiffuseLighting");
+                input(&mut e, "in", i);
+                e.set_attrib("surfaceScale", number(scale));
+                e.set_attrib("diffuseConstant", number(constant));
+                e.set_attrib("lighting-color", Color { alpha: 1., ..*c });
+                e.add_child(light.to_element());
+            },
+            Primitive::SpecularLighting(ref i, scale, constant, exponent, ref c, ref light) => {
+                e = Element::new("feSpecularLighting");
+                input(&mut e, "in", i);
+                e.set_attrib("surfaceScale", number(scale));
+                e.set_attrib("specularConstant", number(constant));
+                e.set_attrib("specularExponent", number(exponent));
+                e.set_attrib("lighting-color", Color { alpha: 1., ..*c });
+                e.add_child(light.to_element());
+            }
+        }
+        e
+    }
+}
+
+impl TransferFunction {
+    fn to_element(&self, name: &str) -> Element {
+        fn values(values: &Vec<f64>) -> String {
+            let values: Vec<String> = values.iter().map(|v| number(*v)).collect();
+            values.connect(" ")
+        }
+        let mut e = Element::new(name);
+        match *self {
+            TransferFunction::Identity                    => e.set_attrib("type", "identity"),
+            TransferFunction::Table(ref v)                => {
+                e.set_attrib("type", "table");
+                e.set_attrib("tableValues", values(v));
+            },
+            TransferFunction::Discrete(ref v)             => {
+                e.set_attrib("type", "discrete");
+                e.set_attrib("tableValues", values(v));
+            },
+            TransferFunction::Linear(slope, intercept)    => {
+                e.set_attrib("type", "linear");
+                e.set_attrib("slope", number(slope));
+                e.set_attrib("intercept", number(intercept));
+            },
+            TransferFunction::Gamma(amplitude, exponent, offset) => {
+                e.set_attrib("type", "gamma");
+                e.set_attrib("amplitude", number(amplitude));
+                e.set_attrib("exponent", number(exponent));
+                e.set_attrib("offset", number(offset));
+            }
+        }
+        e
+    }
+}
+
+impl LightSource {
+    fn to_element(&self) -> Element {
+        let mut e;
+        match *self {
+            LightSource::Distant(azimuth, elevation)            => {
+                e = Element::new("feDistantLight");
+                e.set_attrib("azimuth", number(azimuth));
+                e.set_attrib("elevation", number(elevation));
+            },
+            LightSource::Point(x, y, z)                         => {
+                e = Element::new("fePointLight");
+                e.set_attrib("x", number(x));
+                e.set_attrib("y", number(y));
+                e.set_attrib("z", number(z));
+            },
+            LightSource::Spot((x, y, z), (px, py, pz), exponent, cone) => {
+                e = Element::new("feSpotLight");
+                e.set_attrib("x", number(x));
+                e.set_attrib("y", number(y));
+                e.set_attrib("z", number(z));
+                e.set_attrib("pointsAtX", number(px));
+                e.set_attrib("pointsAtY", number(py));
+                e.set_attrib("pointsAtZ", number(pz));
+                e.set_attrib("specularExponent", number(exponent));
+                if let Some(a) = cone { e.set_attrib("limitingConeAngle", number(a)) }
+            }
+        }
+        e
+    }
+}
+
+impl Filter {
+    pub fn new() -> Filter {
+        Filter {
+            region: None,
+            units: None,
+            primitive_units: None,
+            attribs: Attributes::new(),
+            primitives: Vec::new()
+        }
+    }
+
+    /// The `x`, `y`, `width` and `height` of the filter effects region, e.g.
+    /// `-10%`, `-10%`, `120%` and `120%`.
+    pub fn region<L: Into<Length>>(&mut self, x: L, y: L, width: L, height: L) -> &mut Filter {
+        self.region = Some((x.into(), y.into(), width.into(), height.into()));
+        self
+    }
+
+    /// Value of `filterUnits`, `objectBoundingBox` by default.
+    pub fn units(&mut self, units: Units) -> &mut Filter {
+        self.units = Some(units);
+        self
+    }
+
+    /// Value of `primitiveUnits`, `userSpaceOnUse` by default.
+    pub fn primitive_units(&mut self, units: Units) -> &mut Filter {
+        self.primitive_units = Some(units);
+        self
+    }
+
+    /// Append `primitive`, its output being named `result` if given. Fails
+    /// when the primitive reads a result which is not defined yet, when
+    /// `result` is already used, or when a color matrix does not have 20
+    /// values.
+    pub fn add(&mut self,
+               primitive: Primitive,
+               result: Option<&str>) -> Result<&mut Filter, FilterError> {
+        if let Primitive::ColorMatrix(_, ColorMatrix::Matrix(ref values)) = primitive {
+            if values.len() != 20 {
+                return error(format!("a color matrix has 20 values, not {}", values.len()))
+            }
+        }
+        for input in primitive.inputs().iter() {
+            if let Input::Result(ref name) = **input {
+                if !self.has_result(name.as_slice()) {
+                    return error(format!("unknown filter result `{}`", name))
+                }
+            }
+        }
+        if let Some(name) = result {
+            if name.is_empty() || name.contains_char(' ') {
+                return error(format!("invalid filter result name `{}`", name))
+            }
+            if self.has_result(name) {
+                return error(format!("duplicate filter result `{}`", name))
+            }
+        }
+        self.primitives.push((primitive, result.map(|r| r.to_string())));
+        Ok(self)
+    }
+
+    fn has_result(&self, name: &str) -> bool {
+        self.primitives.iter().any(|&(_, ref r)| match *r {
+            Some(ref r) => r.as_slice() == name,
+            None        => false
+        })
+    }
+
+    pub fn primitives(&self) -> &Vec<(Primitive, Option<String>)> {
+        &self.primitives
+    }
+
+    /// A shadow of the alpha channel, blurred by `blur` and offset by
+    /// (`dx`, `dy`), drawn under the entity.
+    pub fn drop_shadow(dx: f64, dy: f64, blur: f64, color: &Color) -> Filter {
+        let mut f = Filter::new();
+        f.region(-0.5, -0.5, 2., 2.);
+        // wired by construction, the results are all defined before use
+        f.add(Primitive::GaussianBlur(Some(Input::SourceAlpha), blur, blur), None).unwrap()
+         .add(Primitive::Offset(None, dx, dy), Some("offset")).unwrap()
+         .add(Primitive::Flood(*color), None).unwrap()
+         .add(Primitive::Composite(None, Input::Result("offset".to_string()),
+                                   CompositeOperator::In), Some("shadow")).unwrap()
+         .add(Primitive::Merge(vec![Input::Result("shadow".to_string()),
+                                    Input::SourceGraphic]), None).unwrap();
+        f
+    }
+
+    /// A halo of `color` spreading `radius` around the entity.
+    pub fn glow(radius: f64, color: &Color) -> Filter {
+        let mut f = Filter::new();
+        f.region(-0.5, -0.5, 2., 2.);
+        f.add(Primitive::Morphology(Some(Input::SourceAlpha), MorphologyOperator::Dilate,
+                                    radius / 2., radius / 2.), None).unwrap()
+         .add(Primitive::GaussianBlur(None, radius / 2., radius / 2.), Some("halo")).unwrap()
+         .add(Primitive::Flood(*color), None).unwrap()
+         .add(Primitive::Composite(None, Input::Result("halo".to_string()),
+                                   CompositeOperator::In), Some("glow")).unwrap()
+         .add(Primitive::Merge(vec![Input::Result("glow".to_string()),
+                                    Input::SourceGraphic]), None).unwrap();
+        f
+    }
+
+    /// A gaussian blur of the whole entity.
+    pub fn blur(deviation: f64) -> Filter {
+        let mut f = Filter::new();
+        f.add(Primitive::GaussianBlur(None, deviation, deviation), None).unwrap();
+        f
+    }
+}
+
+impl SVGEntity for Filter {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("filter");
+        if let Some((x, y, width, height)) = self.region {
+            e.set_attrib("x", x);
+            e.set_attrib("y", y);
+            e.set_attrib("width", width);
+            e.set_attrib("height", height);
+        }
+        if let Some(ref u) = self.units { e.set_attrib("filterUnits", u) }
+        if let Some(ref u) = self.primitive_units { e.set_attrib("primitiveUnits", u) }
+        insert_attribs(&mut e, &self.attribs);
+        for &(ref primitive, ref result) in self.primitives.iter() {
+            let mut p = primitive.to_element();
+            if let Some(ref r) = *result { p.set_attrib("result", r) }
+            e.add_child(p);
+        }
+        e
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            Input::SourceGraphic   => "SourceGraphic",
+            Input::SourceAlpha     => "SourceAlpha",
+            Input::BackgroundImage => "BackgroundImage",
+            Input::BackgroundAlpha => "BackgroundAlpha",
+            Input::FillPaint       => "FillPaint",
+            Input::StrokePaint     => "StrokePaint",
+            Input::Result(ref r)   => r.as_slice()
+        })
+    }
+}
+
+impl fmt::Display for CompositeOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            CompositeOperator::Over          => "over",
+            CompositeOperator::In            => "in",
+            CompositeOperator::Out           => "out",
+            CompositeOperator::Atop          => "atop",
+            CompositeOperator::Xor           => "xor",
+            CompositeOperator::Arithmetic(..) => "arithmetic"
+        })
+    }
+}
+
+impl fmt::Display for BlendMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            BlendMode::Normal   => "normal",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen   => "screen",
+            BlendMode::Darken   => "darken",
+            BlendMode::Lighten  => "lighten"
+        })
+    }
+}
+
+impl fmt::Display for MorphologyOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            MorphologyOperator::Erode  => "erode",
+            MorphologyOperator::Dilate => "dilate"
+        })
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            Channel::R => "R",
+            Channel::G => "G",
+            Channel::B => "B",
+            Channel::A => "A"
+        })
+    }
+}
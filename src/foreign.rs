@@ -0,0 +1,91 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! `<foreignObject>`, to embed content from another namespace such as HTML.
+
+use std::convert::Into;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::{insert_attribs, insert_transform, number};
+use document::{Element, Node};
+use length::Length;
+use parser::{ParseError, parse_fragment};
+use transform::Transform;
+use SVGEntity;
+
+static XHTML_NS: &'static str = "http://www.w3.org/1999/xhtml";
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForeignObject {
+    pub x: f64,
+    pub y: f64,
+    pub width: Length,
+    pub height: Length,
+    pub attribs: Attributes,
+    pub transform: Option<Transform>,
+    pub children: Vec<Node>
+}
+
+impl ForeignObject {
+    pub fn new<L: Into<Length>>(x: f64, y: f64, width: L, height: L) -> ForeignObject {
+        ForeignObject {
+            x: x,
+            y: y,
+            width: width.into(),
+            height: height.into(),
+            attribs: Attributes::new(),
+            transform: None,
+            children: Vec::new()
+        }
+    }
+
+    /// A foreign object holding the HTML `fragment`, which must be well
+    /// formed XML. Its top level elements are put in the XHTML namespace.
+    pub fn html<L: Into<Length>>(x: f64,
+                                 y: f64,
+                                 width: L,
+                                 height: L,
+                fragment: &str) -> Result<ForeignObject, ParseError> {
+        let mut object = ForeignObject::new(x, y, width, height);
+        object.children = try!(parse_fragment(fragment));
+        for node in object.children.iter_mut() {
+            if let Node::Element(ref mut e) = *node {
+                if e.get_attrib("xmlns").is_none() { e.set_attrib("xmlns", XHTML_NS) }
+            }
+        }
+        Ok(object)
+    }
+}
+
+impl SVGEntity for ForeignObject {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("foreignObject");
+        e.set_attrib("x", number(self.x));
+        e.set_attrib("y", number(self.y));
+        e.set_attrib("width", self.width);
+        e.set_attrib("height", self.height);
+        insert_transform(&mut e, &self.transform);
+        insert_attribs(&mut e, &self.attribs);
+        e.children = self.children.clone();
+        e
+    }
+}
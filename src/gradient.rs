@@ -0,0 +1,283 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Linear and radial gradient paint servers.
+
+use std::fmt;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use color::Color;
+use common::{insert_attribs, number};
+use defs::{Handle, Units};
+use document::Element;
+use style::Opacity;
+use transform::Transform;
+use SVGEntity;
+
+/// A color stop of a gradient, `offset` is in `[0, 1]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stop {
+    pub offset: f64,
+    pub color: Color,
+    pub opacity: Option<Opacity>
+}
+
+/// What happens outside of the gradient vector.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SpreadMethod {
+    Pad,
+    Reflect,
+    Repeat
+}
+
+/// A `<linearGradient>`. Attributes left to `None` take their value from the
+/// gradient referenced by `href`, or the default of the svg specification.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LinearGradient {
+    pub x1: Option<f64>,
+    pub y1: Option<f64>,
+    pub x2: Option<f64>,
+    pub y2: Option<f64>,
+    pub stops: Vec<Stop>,
+    pub units: Option<Units>,
+    pub transform: Option<Transform>,
+    pub spread: Option<SpreadMethod>,
+    pub href: Option<Handle>,
+    pub attribs: Attributes
+}
+
+/// A `<radialGradient>`, see `LinearGradient` for the inheritance of the
+/// attributes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RadialGradient {
+    pub cx: Option<f64>,
+    pub cy: Option<f64>,
+    pub r: Option<f64>,
+    pub fx: Option<f64>,
+    pub fy: Option<f64>,
+    pub stops: Vec<Stop>,
+    pub units: Option<Units>,
+    pub transform: Option<Transform>,
+    pub spread: Option<SpreadMethod>,
+    pub href: Option<Handle>,
+    pub attribs: Attributes
+}
+
+impl Stop {
+    /// A stop at `offset`, clamped to `[0, 1]`.
+    pub fn new(offset: f64, color: Color) -> Stop {
+        Stop {
+            offset: if offset < 0. { 0. } else if offset > 1. { 1. } else { offset },
+            color: color,
+            opacity: None
+        }
+    }
+}
+
+impl SVGEntity for Stop {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("stop");
+        e.set_attrib("offset", number(self.offset));
+        // stop-color takes no alpha, it is merged into stop-opacity
+        e.set_attrib("stop-color", Color { alpha: 1., ..self.color });
+        let opacity = self.opacity.map(|o| o.get()).unwrap_or(1.) * self.color.alpha;
+        if opacity != 1. {
+            e.set_attrib("stop-opacity", number(opacity));
+        }
+        e
+    }
+}
+
+// attributes shared by both kinds of gradients
+fn insert_common(e: &mut Element,
+                 units: &Option<Units>,
+                 transform: &Option<Transform>,
+                 spread: &Option<SpreadMethod>,
+                 href: &Option<Handle>) {
+    if let Some(ref u) = *units { e.set_attrib("gradientUnits", u) }
+    match *transform {
+        Some(ref t) if !t.is_empty() => e.set_attrib("gradientTransform", t.value()),
+        _                            => {/* nothing to do */}
+    }
+    if let Some(ref s) = *spread { e.set_attrib("spreadMethod", s) }
+    if let Some(ref h) = *href { e.set_attrib("xlink:href", h.href()) }
+}
+
+fn insert_number(e: &mut Element, name: &str, value: Option<f64>) {
+    if let Some(v) = value { e.set_attrib(name, number(v)) }
+}
+
+impl LinearGradient {
+    pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> LinearGradient {
+        LinearGradient {
+            x1: Some(x1),
+            y1: Some(y1),
+            x2: Some(x2),
+            y2: Some(y2),
+            ..LinearGradient::inherit(None)
+        }
+    }
+
+    /// A gradient taking everything it does not set from `href`.
+    pub fn inherit(href: Option<&Handle>) -> LinearGradient {
+        LinearGradient {
+            x1: None,
+            y1: None,
+            x2: None,
+            y2: None,
+            stops: Vec::new(),
+            units: None,
+            transform: None,
+            spread: None,
+            href: href.cloned(),
+            attribs: Attributes::new()
+        }
+    }
+
+    pub fn stop(&mut self, offset: f64, color: Color) -> &mut LinearGradient {
+        self.stops.push(Stop::new(offset, color));
+        self
+    }
+
+    pub fn add_stop(&mut self, stop: Stop) -> &mut LinearGradient {
+        self.stops.push(stop);
+        self
+    }
+
+    pub fn units(&mut self, units: Units) -> &mut LinearGradient {
+        self.units = Some(units);
+        self
+    }
+
+    pub fn transform(&mut self, transform: Transform) -> &mut LinearGradient {
+        self.transform = Some(transform);
+        self
+    }
+
+    pub fn spread(&mut self, spread: SpreadMethod) -> &mut LinearGradient {
+        self.spread = Some(spread);
+        self
+    }
+}
+
+impl RadialGradient {
+    pub fn new(cx: f64, cy: f64, r: f64) -> RadialGradient {
+        RadialGradient {
+            cx: Some(cx),
+            cy: Some(cy),
+            r: Some(r),
+            ..RadialGradient::inherit(None)
+        }
+    }
+
+    /// A gradient taking everything it does not set from `href`.
+    pub fn inherit(href: Option<&Handle>) -> RadialGradient {
+        RadialGradient {
+            cx: None,
+            cy: None,
+            r: None,
+            fx: None,
+            fy: None,
+            stops: Vec::new(),
+            units: None,
+            transform: None,
+            spread: None,
+            href: href.cloned(),
+            attribs: Attributes::new()
+        }
+    }
+
+    /// Move the focal point, which is the center by default.
+    pub fn focus(&mut self, fx: f64, fy: f64) -> &mut RadialGradient {
+        self.fx = Some(fx);
+        self.fy = Some(fy);
+        self
+    }
+
+    pub fn stop(&mut self, offset: f64, color: Color) -> &mut RadialGradient {
+        self.stops.push(Stop::new(offset, color));
+        self
+    }
+
+    pub fn add_stop(&mut self, stop: Stop) -> &mut RadialGradient {
+        self.stops.push(stop);
+        self
+    }
+
+    pub fn units(&mut self, units: Units) -> &mut RadialGradient {
+        self.units = Some(units);
+        self
+    }
+
+    pub fn transform(&mut self, transform: Transform) -> &mut RadialGradient {
+        self.transform = Some(transform);
+        self
+    }
+
+    pub fn spread(&mut self, spread: SpreadMethod) -> &mut RadialGradient {
+        self.spread = Some(spread);
+        self
+    }
+}
+
+impl SVGEntity for LinearGradient {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("linearGradient");
+        insert_number(&mut e, "x1", self.x1);
+        insert_number(&mut e, "y1", self.y1);
+        insert_number(&mut e, "x2", self.x2);
+        insert_number(&mut e, "y2", self.y2);
+        insert_common(&mut e, &self.units, &self.transform, &self.spread, &self.href);
+        insert_attribs(&mut e, &self.attribs);
+        for stop in self.stops.iter() {
+            e.add_child(stop.to_element());
+        }
+        e
+    }
+}
+
+impl SVGEntity for RadialGradient {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("radialGradient");
+        insert_number(&mut e, "cx", self.cx);
+        insert_number(&mut e, "cy", self.cy);
+        insert_number(&mut e, "r", self.r);
+        insert_number(&mut e, "fx", self.fx);
+        insert_number(&mut e, "fy", self.fy);
+        insert_common(&mut e, &self.units, &self.transform, &self.spread, &self.href);
+        insert_attribs(&mut e, &self.attribs);
+        for stop in self.stops.iter() {
+            e.add_child(stop.to_element());
+        }
+        e
+    }
+}
+
+impl fmt::Display for SpreadMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            SpreadMethod::Pad     => "pad",
+            SpreadMethod::Reflect => "reflect",
+            SpreadMethod::Repeat  => "repeat"
+        })
+    }
+}
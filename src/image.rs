@@ -0,0 +1,269 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Raster images, referenced by url or embedded as a base64 data URI.
+
+use std::convert::{AsRef, Into};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path as FilePath;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::{insert_attribs, insert_transform, number};
+use document::Element;
+use length::Length;
+use style::Style;
+use transform::Transform;
+use viewport::PreserveAspectRatio;
+use SVGEntity;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImageError {
+    pub desc: String
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.desc)
+    }
+}
+
+impl Error for ImageError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+fn error<T>(desc: String) -> Result<T, ImageError> {
+    Err(ImageError { desc: desc })
+}
+
+/// The formats which can be embedded.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    WebP
+}
+
+/// An `<image>`. Its width and height are those of the image file when it is
+/// embedded and none are given.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Image {
+    pub x: f64,
+    pub y: f64,
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub href: String,
+    pub preserve_aspect_ratio: Option<PreserveAspectRatio>,
+    pub style: Style,
+    pub attribs: Attributes,
+    pub transform: Option<Transform>
+}
+
+static BASE64: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut o = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0],
+                 if chunk.len() > 1 { chunk[1] } else { 0 },
+                 if chunk.len() > 2 { chunk[2] } else { 0 }];
+        let n = (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize;
+        o.push(BASE64[n >> 18] as char);
+        o.push(BASE64[(n >> 12) & 63] as char);
+        o.push(if chunk.len() > 1 { BASE64[(n >> 6) & 63] as char } else { '=' });
+        o.push(if chunk.len() > 2 { BASE64[n & 63] as char } else { '=' });
+    }
+    o
+}
+
+fn be16(data: &[u8], i: usize) -> u32 {
+    (data[i] as u32) << 8 | data[i + 1] as u32
+}
+
+fn le16(data: &[u8], i: usize) -> u32 {
+    data[i] as u32 | (data[i + 1] as u32) << 8
+}
+
+fn le24(data: &[u8], i: usize) -> u32 {
+    le16(data, i) | (data[i + 2] as u32) << 16
+}
+
+// size given by the first start of frame segment
+fn jpeg_size(data: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    while i + 9 < data.len() {
+        if data[i] != 0xff { return None }
+        let marker = data[i + 1];
+        match marker {
+            0xff                             => { i += 1; continue },
+            0xd0...0xd7 | 0x01               => { i += 2; continue },
+            0xc0...0xcf if marker != 0xc4 && marker != 0xc8 && marker != 0xcc => {
+                return Some((be16(data, i + 7), be16(data, i + 5)))
+            },
+            _                                => i += 2 + be16(data, i + 2) as usize
+        }
+    }
+    None
+}
+
+fn webp_size(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 30 { return None }
+    let chunk = &data[12..16];
+    if chunk == &b"VP8 "[..] && &data[23..26] == &b"\x9d\x01\x2a"[..] {
+        // lossy
+        Some((le16(data, 26) & 0x3fff, le16(data, 28) & 0x3fff))
+    } else if chunk == &b"VP8L"[..] && data[20] == 0x2f {
+        // lossless, 14 bits per dimension minus one
+        let bits = le16(data, 21) | le16(data, 23) << 16;
+        Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+    } else if chunk == &b"VP8X"[..] {
+        // extended, 24 bits per dimension minus one
+        Some((le24(data, 24) + 1, le24(data, 27) + 1))
+    } else {
+        None
+    }
+}
+
+impl ImageFormat {
+    /// Recognize the format of `data` from its signature.
+    pub fn detect(data: &[u8]) -> Option<ImageFormat> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if data.starts_with(b"\xff\xd8\xff") {
+            Some(ImageFormat::Jpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == &b"WEBP"[..] {
+            Some(ImageFormat::WebP)
+        } else {
+            None
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match *self {
+            ImageFormat::Png  => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif  => "image/gif",
+            ImageFormat::WebP => "image/webp"
+        }
+    }
+
+    /// Width and height in pixels read from the header of `data`.
+    pub fn size(&self, data: &[u8]) -> Option<(u32, u32)> {
+        match *self {
+            ImageFormat::Png if data.len() >= 24 && &data[12..16] == &b"IHDR"[..] => {
+                Some((be16(data, 16) << 16 | be16(data, 18), be16(data, 20) << 16 | be16(data, 22)))
+            },
+            ImageFormat::Gif if data.len() >= 10                           => {
+                Some((le16(data, 6), le16(data, 8)))
+            },
+            ImageFormat::Jpeg                                              => jpeg_size(data),
+            ImageFormat::WebP                                              => webp_size(data),
+            _                                                              => None
+        }
+    }
+}
+
+impl Image {
+    /// An image referencing the external file `href`.
+    pub fn new<L: Into<Length>>(x: f64, y: f64, width: L, height: L, href: &str) -> Image {
+        Image {
+            x: x,
+            y: y,
+            width: Some(width.into()),
+            height: Some(height.into()),
+            href: href.to_string(),
+            preserve_aspect_ratio: None,
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None
+        }
+    }
+
+    /// An image embedding `data`, a PNG, JPEG, GIF or WebP file, sized after
+    /// the dimensions read from its header.
+    pub fn embed(x: f64, y: f64, data: &[u8]) -> Result<Image, ImageError> {
+        let format = match ImageFormat::detect(data) {
+            Some(f) => f,
+            None    => return error("unknown image format".to_string())
+        };
+        let (width, height) = match format.size(data) {
+            Some(size) => size,
+            None       => return error(format!("cannot read the size of the {} image",
+                                               format.mime_type()))
+        };
+        Ok(Image {
+            x: x,
+            y: y,
+            width: Some(Length::user(width as f64)),
+            height: Some(Length::user(height as f64)),
+            href: format!("data:{};base64,{}", format.mime_type(), base64(data)),
+            preserve_aspect_ratio: None,
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None
+        })
+    }
+
+    /// Embed the image file at `path`.
+    pub fn open<P: AsRef<FilePath>>(x: f64, y: f64, path: P) -> Result<Image, ImageError> {
+        let path = path.as_ref();
+        let mut data = Vec::new();
+        match File::open(path).and_then(|mut f| f.read_to_end(&mut data)) {
+            Ok(_)  => Image::embed(x, y, data.as_slice()),
+            Err(e) => error(format!("cannot read {}: {}", path.display(), e))
+        }
+    }
+
+    pub fn size<L: Into<Length>>(&mut self, width: L, height: L) -> &mut Image {
+        self.width = Some(width.into());
+        self.height = Some(height.into());
+        self
+    }
+
+    pub fn preserve_aspect_ratio(&mut self, aspect: PreserveAspectRatio) -> &mut Image {
+        self.preserve_aspect_ratio = Some(aspect);
+        self
+    }
+}
+
+impl SVGEntity for Image {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("image");
+        e.set_attrib("x", number(self.x));
+        e.set_attrib("y", number(self.y));
+        if let Some(w) = self.width { e.set_attrib("width", w) }
+        if let Some(h) = self.height { e.set_attrib("height", h) }
+        if let Some(ref p) = self.preserve_aspect_ratio { e.set_attrib("preserveAspectRatio", p) }
+        e.set_attrib("xlink:href", self.href.as_slice());
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
+    }
+}
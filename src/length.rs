@@ -0,0 +1,190 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Lengths with units, and their conversion to pixels.
+
+use std::convert::From;
+use std::error::Error;
+use std::fmt;
+use std::num::Float;
+use std::str::FromStr;
+
+use common::number;
+
+/// The resolution of the CSS reference pixel, used by default to convert
+/// absolute units.
+pub static DEFAULT_DPI: f64 = 96.;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LengthError {
+    pub desc: String
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.desc)
+    }
+}
+
+impl Error for LengthError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LengthUnit {
+    /// user units, written without a suffix
+    User,
+    Px,
+    Pt,
+    Pc,
+    Mm,
+    Cm,
+    In,
+    Em,
+    Ex,
+    Percent
+}
+
+/// A length such as `12cm`, `1.5em` or `50%`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Length {
+    pub value: f64,
+    pub unit: LengthUnit
+}
+
+static UNITS: [(&'static str, LengthUnit); 9] = [
+    ("px", LengthUnit::Px),
+    ("pt", LengthUnit::Pt),
+    ("pc", LengthUnit::Pc),
+    ("mm", LengthUnit::Mm),
+    ("cm", LengthUnit::Cm),
+    ("in", LengthUnit::In),
+    ("em", LengthUnit::Em),
+    ("ex", LengthUnit::Ex),
+    ("%", LengthUnit::Percent)
+];
+
+impl LengthUnit {
+    pub fn suffix(&self) -> &'static str {
+        match *self {
+            LengthUnit::User => "",
+            unit             => UNITS.iter().find(|&&(_, u)| u == unit).unwrap().0
+        }
+    }
+
+    // size of the unit in inches, for the absolute units
+    fn inches(&self) -> Option<f64> {
+        match *self {
+            LengthUnit::Pt => Some(1. / 72.),
+            LengthUnit::Pc => Some(1. / 6.),
+            LengthUnit::Mm => Some(1. / 25.4),
+            LengthUnit::Cm => Some(1. / 2.54),
+            LengthUnit::In => Some(1.),
+            _              => None
+        }
+    }
+}
+
+impl Length {
+    pub fn new(value: f64, unit: LengthUnit) -> Length {
+        Length {
+            value: value,
+            unit: unit
+        }
+    }
+
+    pub fn user(value: f64) -> Length { Length::new(value, LengthUnit::User) }
+
+    pub fn px(value: f64) -> Length { Length::new(value, LengthUnit::Px) }
+
+    pub fn pt(value: f64) -> Length { Length::new(value, LengthUnit::Pt) }
+
+    pub fn mm(value: f64) -> Length { Length::new(value, LengthUnit::Mm) }
+
+    pub fn cm(value: f64) -> Length { Length::new(value, LengthUnit::Cm) }
+
+    pub fn inches(value: f64) -> Length { Length::new(value, LengthUnit::In) }
+
+    pub fn em(value: f64) -> Length { Length::new(value, LengthUnit::Em) }
+
+    pub fn percent(value: f64) -> Length { Length::new(value, LengthUnit::Percent) }
+
+    /// This length in pixels at `dpi` dots per inch, user units being pixels.
+    /// `font_size`, in pixels, is used for `em` and `ex`, the latter taken as
+    /// half an em. Percentages have no absolute size and give `None`.
+    pub fn to_px(&self, dpi: f64, font_size: f64) -> Option<f64> {
+        match self.unit {
+            LengthUnit::User | LengthUnit::Px => Some(self.value),
+            LengthUnit::Em                    => Some(self.value * font_size),
+            LengthUnit::Ex                    => Some(self.value * font_size / 2.),
+            LengthUnit::Percent               => None,
+            unit                              => unit.inches().map(|i| self.value * i * dpi)
+        }
+    }
+
+    /// This length expressed in `unit`, see `to_px` for the parameters.
+    pub fn convert(&self, unit: LengthUnit, dpi: f64, font_size: f64) -> Option<Length> {
+        if unit == self.unit { return Some(*self) }
+        let px = match self.to_px(dpi, font_size) {
+            Some(px) => px,
+            None     => return None
+        };
+        let value = match unit {
+            LengthUnit::User | LengthUnit::Px => px,
+            LengthUnit::Em                    => px / font_size,
+            LengthUnit::Ex                    => px * 2. / font_size,
+            LengthUnit::Percent               => return None,
+            unit                              => px / (unit.inches().unwrap() * dpi)
+        };
+        Some(Length::new(value, unit))
+    }
+}
+
+/// A number alone is a length in user units.
+impl From<f64> for Length {
+    fn from(value: f64) -> Length {
+        Length::user(value)
+    }
+}
+
+impl FromStr for Length {
+    type Err = LengthError;
+
+    fn from_str(s: &str) -> Result<Length, LengthError> {
+        let s = s.trim();
+        let (value, unit) = match UNITS.iter().find(|&&(suffix, _)| s.ends_with(suffix)) {
+            Some(&(suffix, unit)) => (&s[..s.len() - suffix.len()], unit),
+            None                  => (s, LengthUnit::User)
+        };
+        match value.parse::<f64>() {
+            Ok(v) if v.is_finite() => Ok(Length::new(v, unit)),
+            _                      => Err(LengthError { desc: format!("invalid length `{}`", s) })
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", number(self.value), self.unit.suffix())
+    }
+}
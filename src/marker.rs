@@ -0,0 +1,265 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Markers drawn at the vertices of lines, polylines, polygons and paths, and
+//! a small library of common arrowheads.
+
+use std::convert::Into;
+use std::fmt;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use color::Color;
+use common::{insert_attribs, number};
+use container::Container;
+use defs::Handle;
+use document::Element;
+use length::Length;
+use path::Data;
+use viewport::ViewBox;
+use SVGEntity;
+
+/// Value of `orient`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Orient {
+    /// along the direction of the path
+    Auto,
+    /// like `Auto`, but reversed at the start of the path, so the same arrow
+    /// points outwards at both ends
+    AutoStartReverse,
+    /// fixed angle in degrees
+    Angle(f64)
+}
+
+/// Value of `markerUnits`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MarkerUnits {
+    /// scaled with the stroke width of the entity
+    StrokeWidth,
+    UserSpaceOnUse
+}
+
+/// A `<marker>`, its content is drawn with the shape API of `Container`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Marker {
+    pub ref_x: f64,
+    pub ref_y: f64,
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub orient: Option<Orient>,
+    pub units: Option<MarkerUnits>,
+    pub view_box: Option<ViewBox>,
+    pub attribs: Attributes,
+    pub children: Vec<Element>
+}
+
+/// The markers of a line, polyline, polygon or path.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Markers {
+    pub start: Option<Handle>,
+    pub mid: Option<Handle>,
+    pub end: Option<Handle>
+}
+
+/// The markers known by `SVG::builtin_marker`, drawn in a 10 by 10 box.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BuiltinMarker {
+    Arrow,
+    OpenArrow,
+    Dot,
+    Square,
+    Diamond,
+    Bar
+}
+
+impl Marker {
+    /// An empty marker whose point (`ref_x`, `ref_y`) is placed on the vertex.
+    pub fn new(ref_x: f64, ref_y: f64) -> Marker {
+        Marker {
+            ref_x: ref_x,
+            ref_y: ref_y,
+            width: None,
+            height: None,
+            orient: None,
+            units: None,
+            view_box: None,
+            attribs: Attributes::new(),
+            children: Vec::new()
+        }
+    }
+
+    /// Value of `markerWidth` and `markerHeight`, 3 by default.
+    pub fn size<L: Into<Length>>(&mut self, width: L, height: L) -> &mut Marker {
+        self.width = Some(width.into());
+        self.height = Some(height.into());
+        self
+    }
+
+    pub fn orient(&mut self, orient: Orient) -> &mut Marker {
+        self.orient = Some(orient);
+        self
+    }
+
+    pub fn units(&mut self, units: MarkerUnits) -> &mut Marker {
+        self.units = Some(units);
+        self
+    }
+
+    pub fn view_box(&mut self, view_box: ViewBox) -> &mut Marker {
+        self.view_box = Some(view_box);
+        self
+    }
+}
+
+impl Markers {
+    pub fn new() -> Markers {
+        Default::default()
+    }
+
+    /// The same marker at every vertex.
+    pub fn all(marker: &Handle) -> Markers {
+        Markers {
+            start: Some(marker.clone()),
+            mid: Some(marker.clone()),
+            end: Some(marker.clone())
+        }
+    }
+
+    pub fn start(&mut self, marker: &Handle) -> &mut Markers {
+        self.start = Some(marker.clone());
+        self
+    }
+
+    pub fn mid(&mut self, marker: &Handle) -> &mut Markers {
+        self.mid = Some(marker.clone());
+        self
+    }
+
+    pub fn end(&mut self, marker: &Handle) -> &mut Markers {
+        self.end = Some(marker.clone());
+        self
+    }
+
+    /// Set the `marker-start`, `marker-mid` and `marker-end` attributes of
+    /// `element`.
+    pub fn apply(&self, element: &mut Element) {
+        if let Some(ref m) = self.start { element.set_attrib("marker-start", m.url()) }
+        if let Some(ref m) = self.mid { element.set_attrib("marker-mid", m.url()) }
+        if let Some(ref m) = self.end { element.set_attrib("marker-end", m.url()) }
+    }
+}
+
+impl BuiltinMarker {
+    fn name(&self) -> &'static str {
+        match *self {
+            BuiltinMarker::Arrow     => "arrow",
+            BuiltinMarker::OpenArrow => "open-arrow",
+            BuiltinMarker::Dot       => "dot",
+            BuiltinMarker::Square    => "square",
+            BuiltinMarker::Diamond   => "diamond",
+            BuiltinMarker::Bar       => "bar"
+        }
+    }
+
+    /// The id of this marker in `color`, the same marker and color always
+    /// give the same id so it is only defined once.
+    pub fn id(&self, color: &Color) -> String {
+        let hex = color.to_hex();
+        format!("marker-{}-{}", self.name(), &hex[1..])
+    }
+
+    /// The definition of this marker in `color`, sized relatively to the
+    /// stroke width.
+    pub fn marker(&self, color: &Color) -> Marker {
+        let (ref_x, ref_y, orient) = match *self {
+            BuiltinMarker::Arrow     => (10., 5., Orient::AutoStartReverse),
+            BuiltinMarker::OpenArrow => (9., 5., Orient::AutoStartReverse),
+            _                        => (5., 5., Orient::Auto)
+        };
+        let mut m = Marker::new(ref_x, ref_y);
+        // the view box and the attribute strings below are well formed, unwrap
+        // cannot fail
+        m.size(6., 6.).orient(orient).view_box(ViewBox::new(0., 0., 10., 10.).unwrap());
+        let fill = format!("fill=\"{}\"", color);
+        match *self {
+            BuiltinMarker::Arrow     => {
+                let mut d = Data::new();
+                d.move_to(0., 0.).line_to(10., 5.).line_to(0., 10.).close();
+                m.path(&d, fill.as_slice()).unwrap()
+            },
+            BuiltinMarker::OpenArrow => {
+                let stroke = format!("fill=none stroke=\"{}\" stroke-width=1.5", color);
+                m.polyline(&vec![(1., 1.), (9., 5.), (1., 9.)], stroke.as_slice()).unwrap()
+            },
+            BuiltinMarker::Dot       => m.circle(5., 5., 4., fill.as_slice()).unwrap(),
+            BuiltinMarker::Square    => m.rect(1., 1., 8., 8., fill.as_slice()).unwrap(),
+            BuiltinMarker::Diamond   => {
+                let points = vec![(5., 0.), (10., 5.), (5., 10.), (0., 5.)];
+                m.polygon(&points, fill.as_slice()).unwrap()
+            },
+            BuiltinMarker::Bar       => m.rect(4., 0., 2., 10., fill.as_slice()).unwrap()
+        }
+        m
+    }
+}
+
+impl Container for Marker {
+    fn add_element(&mut self, element: Element) {
+        self.children.push(element)
+    }
+}
+
+impl SVGEntity for Marker {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("marker");
+        e.set_attrib("refX", number(self.ref_x));
+        e.set_attrib("refY", number(self.ref_y));
+        if let Some(w) = self.width { e.set_attrib("markerWidth", w) }
+        if let Some(h) = self.height { e.set_attrib("markerHeight", h) }
+        if let Some(ref o) = self.orient { e.set_attrib("orient", o) }
+        if let Some(ref u) = self.units { e.set_attrib("markerUnits", u) }
+        if let Some(v) = self.view_box { e.set_attrib("viewBox", v) }
+        insert_attribs(&mut e, &self.attribs);
+        for child in self.children.iter() {
+            e.add_child(child.clone());
+        }
+        e
+    }
+}
+
+impl fmt::Display for Orient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Orient::Auto             => write!(f, "auto"),
+            Orient::AutoStartReverse => write!(f, "auto-start-reverse"),
+            Orient::Angle(a)         => write!(f, "{}", number(a))
+        }
+    }
+}
+
+impl fmt::Display for MarkerUnits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            MarkerUnits::StrokeWidth    => "strokeWidth",
+            MarkerUnits::UserSpaceOnUse => "userSpaceOnUse"
+        })
+    }
+}
@@ -0,0 +1,208 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Color interpolation, palette generation and mapping of numeric values to
+//! colors, e.g. for heatmaps.
+
+use std::f64::consts::PI;
+use std::num::Float;
+use std::vec::Vec;
+
+use color::{Color, ColorError, Hsl, LinearRgb, Oklab};
+
+/// The color space in which colors are interpolated.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Interpolation {
+    /// straight interpolation of the sRGB components
+    Rgb,
+    /// physically correct mix of the light intensities
+    LinearRgb,
+    /// along the shortest arc of the hue circle
+    Hsl,
+    /// perceptually uniform, the default
+    Oklab
+}
+
+/// Maps a numeric domain to colors by interpolating between stops.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColorScale {
+    stops: Vec<(f64, Color)>,
+    interpolation: Interpolation
+}
+
+fn error<T>(desc: String) -> Result<T, ColorError> {
+    Err(ColorError { desc: desc })
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+fn lerp_hue(from: f64, to: f64, t: f64) -> f64 {
+    let mut delta = (to - from) % 360.;
+    if delta > 180. { delta = delta - 360. } else if delta < -180. { delta = delta + 360. }
+    (from + delta * t + 360.) % 360.
+}
+
+// `count` values evenly spread over [0, 1]
+fn steps(count: usize) -> Vec<f64> {
+    let last = if count > 1 { (count - 1) as f64 } else { 1. };
+    (0..count).map(|i| i as f64 / last).collect()
+}
+
+/// The color at `t` in `[0, 1]` between `from` and `to`, `t` is clamped.
+pub fn interpolate(from: &Color, to: &Color, t: f64, interpolation: Interpolation) -> Color {
+    let t = if t < 0. { 0. } else if t > 1. { 1. } else { t };
+    let alpha = lerp(from.alpha, to.alpha, t);
+    match interpolation {
+        Interpolation::Rgb       => {
+            let mix = |a: u8, b: u8| lerp(a as f64, b as f64, t).round() as u8;
+            Color::rgba(mix(from.red, to.red), mix(from.green, to.green), mix(from.blue, to.blue), alpha)
+        },
+        Interpolation::LinearRgb => {
+            let (a, b) = (from.to_linear(), to.to_linear());
+            Color::from_linear(&LinearRgb {
+                red: lerp(a.red, b.red, t),
+                green: lerp(a.green, b.green, t),
+                blue: lerp(a.blue, b.blue, t),
+                alpha: alpha
+            })
+        },
+        Interpolation::Hsl       => {
+            let (a, b) = (from.to_hsl(), to.to_hsl());
+            // the hue of a gray is meaningless, keep the one of the other color
+            let (ha, hb) = match (a.saturation == 0., b.saturation == 0.) {
+                (true, false) => (b.hue, b.hue),
+                (false, true) => (a.hue, a.hue),
+                _             => (a.hue, b.hue)
+            };
+            Color::from_hsl(&Hsl {
+                hue: lerp_hue(ha, hb, t),
+                saturation: lerp(a.saturation, b.saturation, t),
+                lightness: lerp(a.lightness, b.lightness, t),
+                alpha: alpha
+            })
+        },
+        Interpolation::Oklab     => {
+            let (a, b) = (from.to_oklab(), to.to_oklab());
+            Color::from_oklab(&Oklab {
+                lightness: lerp(a.lightness, b.lightness, t),
+                a: lerp(a.a, b.a, t),
+                b: lerp(a.b, b.b, t),
+                alpha: alpha
+            })
+        }
+    }
+}
+
+/// `count` colors going from `from` to `to`, for ordered data.
+pub fn sequential(from: &Color, to: &Color, count: usize, interpolation: Interpolation) -> Vec<Color> {
+    steps(count).iter().map(|&t| interpolate(from, to, t, interpolation)).collect()
+}
+
+/// `count` colors going from `low` to `high` through `middle`, for data
+/// diverging from a meaningful center value.
+pub fn diverging(low: &Color,
+                 middle: &Color,
+                 high: &Color,
+                 count: usize,
+                 interpolation: Interpolation) -> Vec<Color> {
+    steps(count).iter().map(|&t| if t <= 0.5 {
+        interpolate(low, middle, t * 2., interpolation)
+    } else {
+        interpolate(middle, high, t * 2. - 1., interpolation)
+    }).collect()
+}
+
+/// `count` distinct colors of the same lightness and chroma, for categories.
+/// Hues are spread with the golden angle, so the first colors of a palette do
+/// not change when `count` grows.
+pub fn qualitative(count: usize) -> Vec<Color> {
+    let golden_angle = PI * (3. - 5f64.sqrt());
+    (0..count).map(|i| {
+        let hue = 0.5 + i as f64 * golden_angle;
+        Color::from_oklab(&Oklab {
+            lightness: 0.7,
+            a: 0.12 * hue.cos(),
+            b: 0.12 * hue.sin(),
+            alpha: 1.
+        })
+    }).collect()
+}
+
+impl ColorScale {
+    /// A scale spreading `colors` evenly over `[min, max]`.
+    pub fn new(colors: Vec<Color>, min: f64, max: f64) -> Result<ColorScale, ColorError> {
+        if !(min < max) {
+            return error(format!("invalid domain [{}, {}]", min, max))
+        }
+        let stops = steps(colors.len()).iter()
+                                       .map(|&t| lerp(min, max, t))
+                                       .zip(colors.into_iter())
+                                       .collect();
+        ColorScale::with_stops(stops)
+    }
+
+    /// A scale going through each `(value, color)` stop, values must be
+    /// strictly increasing.
+    pub fn with_stops(stops: Vec<(f64, Color)>) -> Result<ColorScale, ColorError> {
+        if stops.len() < 2 {
+            return error("a color scale needs at least two colors".to_string())
+        }
+        for pair in stops.windows(2) {
+            if !(pair[0].0 < pair[1].0) {
+                return error(format!("color scale stops are not increasing: {} then {}",
+                                     pair[0].0, pair[1].0))
+            }
+        }
+        Ok(ColorScale {
+            stops: stops,
+            interpolation: Interpolation::Oklab
+        })
+    }
+
+    pub fn interpolation(&mut self, interpolation: Interpolation) -> &mut ColorScale {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn domain(&self) -> (f64, f64) {
+        (self.stops[0].0, self.stops[self.stops.len() - 1].0)
+    }
+
+    /// The color of `value`, values outside of the domain take the color of
+    /// the nearest end.
+    pub fn map(&self, value: f64) -> Color {
+        let last = self.stops.len() - 1;
+        if value.is_nan() || value <= self.stops[0].0 { return self.stops[0].1 }
+        if value >= self.stops[last].0 { return self.stops[last].1 }
+        let i = self.stops.iter().position(|&(v, _)| value < v).unwrap();
+        let (v0, c0) = self.stops[i - 1];
+        let (v1, c1) = self.stops[i];
+        interpolate(&c0, &c1, (value - v0) / (v1 - v0), self.interpolation)
+    }
+
+    /// `count` colors evenly sampled over the domain, e.g. for a legend.
+    pub fn colors(&self, count: usize) -> Vec<Color> {
+        let (min, max) = self.domain();
+        steps(count).iter().map(|&t| self.map(lerp(min, max, t))).collect()
+    }
+}
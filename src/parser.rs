@@ -0,0 +1,524 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! A small XML parser loading svg files into the document model.
+
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+use std::vec::Vec;
+
+use document::{Element, Node};
+
+/// An error encountered while parsing, positioned at the line and column
+/// (both starting at 1) where it was detected.
+///
+/// Errors raised while reading the input, before any parsing happened, are
+/// reported at line and column 0.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub desc: String
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.desc)
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+// elements in which whitespace only text is meaningful
+static TEXT_ELEMENTS: [&'static str; 3] = ["text", "tspan", "textPath"];
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    // general entities declared in the internal subset of the doctype, the
+    // value of an external entity is not known
+    entities: Vec<(String, Option<String>)>
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == ':'
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_digit(10) || c == '-' || c == '.'
+}
+
+impl Parser {
+    fn new(input: &str) -> Parser {
+        Parser {
+            chars: input.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            entities: Vec::new()
+        }
+    }
+
+    fn error(&self, desc: &str) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            desc: desc.to_string()
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        if self.pos < self.chars.len() { Some(self.chars[self.pos]) } else { None }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        match c {
+            Some('\n') => { self.line += 1; self.column = 1 },
+            Some(_)    => self.column += 1,
+            None       => {/* end of input */}
+        }
+        if c.is_some() { self.pos += 1 }
+        c
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        let mut i = self.pos;
+        for c in s.chars() {
+            if i >= self.chars.len() || self.chars[i] != c { return false }
+            i += 1;
+        }
+        true
+    }
+
+    fn advance(&mut self, n: usize) {
+        for _ in 0..n { self.next(); }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), ParseError> {
+        if self.starts_with(s) {
+            self.advance(s.chars().count());
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", s).as_slice()))
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() { break }
+            self.next();
+        }
+        self.pos != start
+    }
+
+    // consume everything up to and including `end`
+    fn skip_until(&mut self, end: &str, what: &str) -> Result<String, ParseError> {
+        let mut s = String::new();
+        loop {
+            if self.starts_with(end) {
+                self.advance(end.chars().count());
+                return Ok(s)
+            }
+            match self.next() {
+                Some(c) => s.push(c),
+                None    => return Err(self.error(format!("unterminated {}", what).as_slice()))
+            }
+        }
+    }
+
+    // a quoted literal of the doctype, e.g. a system identifier
+    fn skip_literal(&mut self) -> Result<(), ParseError> {
+        let quote = self.next();
+        loop {
+            match self.next() {
+                c if c == quote => return Ok(()),
+                Some(_)         => {/* skip */},
+                None            => return Err(self.error("unterminated literal"))
+            }
+        }
+    }
+
+    // the rest of a markup declaration of the doctype, up to its `>`
+    fn skip_declaration(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                Some('>')                        => { self.next(); return Ok(()) },
+                Some(c) if c == '"' || c == '\'' => try!(self.skip_literal()),
+                Some(_)                          => { self.next(); },
+                None                             => return Err(self.error("unterminated doctype"))
+            }
+        }
+    }
+
+    fn parse_doctype(&mut self) -> Result<(), ParseError> {
+        try!(self.expect("<!DOCTYPE"));
+        loop {
+            match self.peek() {
+                Some('[')                        => { self.next(); try!(self.parse_subset()) },
+                Some('>')                        => { self.next(); return Ok(()) },
+                Some(c) if c == '"' || c == '\'' => try!(self.skip_literal()),
+                Some(_)                          => { self.next(); },
+                None                             => return Err(self.error("unterminated doctype"))
+            }
+        }
+    }
+
+    // The internal subset of the doctype, up to its `]`. Only the entity
+    // declarations are kept, e.g. the namespaces declared by Illustrator.
+    fn parse_subset(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("]") {
+                self.next();
+                return Ok(())
+            } else if self.starts_with("<!ENTITY") {
+                try!(self.parse_entity_declaration());
+            } else if self.starts_with("<!--") {
+                try!(self.skip_until("-->", "comment"));
+            } else if self.starts_with("<?") {
+                try!(self.skip_until("?>", "processing instruction"));
+            } else if self.starts_with("<!") {
+                try!(self.skip_declaration());
+            } else if self.starts_with("%") {
+                try!(self.skip_until(";", "parameter entity reference"));
+            } else if self.peek().is_none() {
+                return Err(self.error("unterminated doctype"))
+            } else {
+                return Err(self.error("unexpected content in the doctype"))
+            }
+        }
+    }
+
+    fn parse_entity_declaration(&mut self) -> Result<(), ParseError> {
+        try!(self.expect("<!ENTITY"));
+        if !self.skip_whitespace() {
+            return Err(self.error("expected whitespace after `<!ENTITY`"))
+        }
+        let parameter = self.peek() == Some('%');
+        if parameter {
+            self.next();
+            self.skip_whitespace();
+        }
+        let name = try!(self.parse_name());
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(c) if c == '"' || c == '\'' => Some(try!(self.parse_entity_value())),
+            _                                => None
+        };
+        // the external identifier, or the whitespace before `>`
+        try!(self.skip_declaration());
+        // the first declaration of an entity is the binding one
+        if !parameter && !self.entities.iter().any(|&(ref n, _)| *n == name) {
+            self.entities.push((name, value));
+        }
+        Ok(())
+    }
+
+    // The quoted replacement text of an entity. The references it contains
+    // are expanded right away, so an entity can only use the ones declared
+    // before it.
+    fn parse_entity_value(&mut self) -> Result<String, ParseError> {
+        let quote = self.next();
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                c if c == quote => { self.next(); return Ok(value) },
+                Some('&')       => value.push_str(try!(self.parse_reference()).as_slice()),
+                Some('%')       => return Err(self.error("parameter entities are not supported")),
+                Some(c)         => { self.next(); value.push(c) },
+                None            => return Err(self.error("unterminated entity value"))
+            }
+        }
+    }
+
+    // comments, processing instructions and whitespace allowed around the root
+    fn skip_misc(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<?") {
+                try!(self.skip_until("?>", "processing instruction"));
+            } else if self.starts_with("<!--") {
+                try!(self.skip_until("-->", "comment"));
+            } else {
+                return Ok(())
+            }
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Element, ParseError> {
+        try!(self.skip_misc());
+        if self.starts_with("<!DOCTYPE") {
+            try!(self.parse_doctype());
+            try!(self.skip_misc());
+        }
+        if self.peek() != Some('<') {
+            return Err(self.error("expected the root element"))
+        }
+        let (line, column) = (self.line, self.column);
+        let root = try!(self.parse_element());
+        if root.name.as_slice() != "svg" {
+            return Err(ParseError {
+                line: line,
+                column: column,
+                desc: format!("expected an <svg> root element, found <{}>", root.name)
+            })
+        }
+        try!(self.skip_misc());
+        match self.peek() {
+            Some(_) => Err(self.error("unexpected content after the root element")),
+            None    => Ok(root)
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<String, ParseError> {
+        let mut name = String::new();
+        match self.peek() {
+            Some(c) if is_name_start(c) => {},
+            _                           => return Err(self.error("expected a name"))
+        }
+        while let Some(c) = self.peek() {
+            if !is_name_char(c) { break }
+            name.push(c);
+            self.next();
+        }
+        Ok(name)
+    }
+
+    // A character or entity reference, replaced by its text. The text of an
+    // entity is not parsed as markup.
+    fn parse_reference(&mut self) -> Result<String, ParseError> {
+        let (line, column) = (self.line, self.column);
+        try!(self.expect("&"));
+        let mut reference = String::new();
+        loop {
+            match self.next() {
+                Some(';')                               => break,
+                Some(c) if is_name_char(c) || c == '#'  => reference.push(c),
+                _                                       => {
+                    return Err(ParseError {
+                        line: line,
+                        column: column,
+                        desc: "unterminated entity reference".to_string()
+                    })
+                }
+            }
+        }
+        let decoded = match reference.as_slice() {
+            "lt"    => Some('<'),
+            "gt"    => Some('>'),
+            "amp"   => Some('&'),
+            "quot"  => Some('"'),
+            "apos"  => Some('\''),
+            r if r.starts_with("#x") => parse_char_ref(&r[2..], 16),
+            r if r.starts_with("#")  => parse_char_ref(&r[1..], 10),
+            _       => None
+        };
+        if let Some(c) = decoded {
+            let mut text = String::new();
+            text.push(c);
+            return Ok(text)
+        }
+        let desc = match self.entities.iter().find(|&&(ref n, _)| *n == reference) {
+            Some(&(_, Some(ref value))) => return Ok(value.clone()),
+            Some(&(_, None))            => format!("external entity `&{};` is not supported",
+                                                   reference),
+            None                        => format!("unknown entity reference `&{};`", reference)
+        };
+        Err(ParseError {
+            line: line,
+            column: column,
+            desc: desc
+        })
+    }
+
+    fn parse_attrib_value(&mut self) -> Result<String, ParseError> {
+        let quote = match self.peek() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _                                => return Err(self.error("expected a quoted value"))
+        };
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                Some(c) if c == quote => { self.next(); return Ok(value) },
+                Some('&')             => value.push_str(try!(self.parse_reference()).as_slice()),
+                Some('<')             => return Err(self.error("`<` is not allowed in attribute values")),
+                Some(c)               => { self.next(); value.push(c) },
+                None                  => return Err(self.error("unterminated attribute value"))
+            }
+        }
+    }
+
+    fn parse_element(&mut self) -> Result<Element, ParseError> {
+        try!(self.expect("<"));
+        let mut element = Element::new(try!(self.parse_name()).as_slice());
+        // attributes
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.advance(2);
+                return Ok(element)
+            }
+            if self.starts_with(">") {
+                self.next();
+                break
+            }
+            if !had_whitespace {
+                return Err(self.error("expected whitespace before attribute"))
+            }
+            let (line, column) = (self.line, self.column);
+            let name = try!(self.parse_name());
+            self.skip_whitespace();
+            try!(self.expect("="));
+            self.skip_whitespace();
+            let value = try!(self.parse_attrib_value());
+            if element.get_attrib(name.as_slice()).is_some() {
+                return Err(ParseError {
+                    line: line,
+                    column: column,
+                    desc: format!("duplicate attribute `{}`", name)
+                })
+            }
+            element.set_attrib(name.as_slice(), value);
+        }
+        // content
+        let keep_whitespace = TEXT_ELEMENTS.iter().any(|n| *n == element.name.as_slice());
+        let mut text = String::new();
+        loop {
+            if self.starts_with("</") || (self.starts_with("<") && !self.starts_with("<![CDATA[")) {
+                if !text.is_empty() {
+                    if keep_whitespace || !text.as_slice().trim().is_empty() {
+                        element.children.push(Node::Text(text.clone()));
+                    }
+                    text.clear();
+                }
+            }
+            if self.starts_with("</") {
+                self.advance(2);
+                let name = try!(self.parse_name());
+                if name != element.name {
+                    return Err(self.error(format!("expected `</{}>`, found `</{}>`",
+                                                  element.name, name).as_slice()))
+                }
+                self.skip_whitespace();
+                try!(self.expect(">"));
+                return Ok(element)
+            } else if self.starts_with("<!--") {
+                try!(self.skip_until("-->", "comment"));
+            } else if self.starts_with("<![CDATA[") {
+                self.advance(9);
+                text.push_str(try!(self.skip_until("]]>", "CDATA section")).as_slice());
+            } else if self.starts_with("<?") {
+                try!(self.skip_until("?>", "processing instruction"));
+            } else if self.starts_with("<") {
+                element.add_child(try!(self.parse_element()));
+            } else {
+                match self.peek() {
+                    Some('&') => text.push_str(try!(self.parse_reference()).as_slice()),
+                    Some(c)   => { self.next(); text.push(c) },
+                    None      => {
+                        return Err(self.error(format!("unclosed element <{}>",
+                                                      element.name).as_slice()))
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_fragment(&mut self) -> Result<Vec<Node>, ParseError> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        loop {
+            let end_of_text = match self.peek() {
+                Some('<') => !self.starts_with("<![CDATA["),
+                Some(_)   => false,
+                None      => true
+            };
+            if end_of_text && !text.is_empty() {
+                nodes.push(Node::Text(text.clone()));
+                text.clear();
+            }
+            if self.peek().is_none() {
+                return Ok(nodes)
+            } else if self.starts_with("</") {
+                return Err(self.error("unexpected closing tag"))
+            } else if self.starts_with("<!--") {
+                try!(self.skip_until("-->", "comment"));
+            } else if self.starts_with("<![CDATA[") {
+                self.advance(9);
+                text.push_str(try!(self.skip_until("]]>", "CDATA section")).as_slice());
+            } else if self.starts_with("<?") {
+                try!(self.skip_until("?>", "processing instruction"));
+            } else if self.starts_with("<") {
+                nodes.push(Node::Element(try!(self.parse_element())));
+            } else if self.peek() == Some('&') {
+                text.push_str(try!(self.parse_reference()).as_slice());
+            } else {
+                text.push(self.next().unwrap());
+            }
+        }
+    }
+}
+
+fn parse_char_ref(digits: &str, radix: u32) -> Option<char> {
+    if digits.is_empty() { return None }
+    let mut code: u32 = 0;
+    for c in digits.chars() {
+        match c.to_digit(radix) {
+            Some(d) if code <= 0x10FFFF => code = code * radix + d,
+            _                           => return None
+        }
+    }
+    ::std::char::from_u32(code)
+}
+
+/// Parse an svg document, returning its root `<svg>` element.
+pub fn parse_str(input: &str) -> Result<Element, ParseError> {
+    Parser::new(input).parse_document()
+}
+
+/// Parse a sequence of elements and text which is not a whole document, e.g.
+/// the HTML content of a `<foreignObject>`.
+pub fn parse_fragment(input: &str) -> Result<Vec<Node>, ParseError> {
+    Parser::new(input).parse_fragment()
+}
+
+/// Read a whole svg document from `reader` and parse it.
+pub fn parse_reader<R: Read>(reader: &mut R) -> Result<Element, ParseError> {
+    let mut s = String::new();
+    match reader.read_to_string(&mut s) {
+        Ok(_)    => parse_str(s.as_slice()),
+        Err(err) => Err(ParseError {
+            line: 0,
+            column: 0,
+            desc: format!("{}", err)
+        })
+    }
+}
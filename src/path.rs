@@ -19,30 +19,670 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use std::fmt::Debug;
-use std::collections::HashMap;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+use std::num::Float;
+use std::str::FromStr;
+use std::vec::Vec;
 
-use common::{insert_attribs, insert_transform, finalize};
+use attribs::Attributes;
+use common::{insert_attribs, insert_transform, number};
+use document::Element;
+use marker::Markers;
+use style::Style;
 use transform::Transform;
 use SVGEntity;
 
-#[derive(Debug, Eq, Clone)]
-pub struct EllipticalArc {
-    pub x: i32,
-    pub y: i32,
-    pub x_radius: u32,
-    pub y_radius: u32,
-    pub x_axis_rotation: i32,
-    pub large_arc: bool,
-    pub sweep: bool,
-    pub close: bool,
-    pub attribs: HashMap<~str, ~str>,
-    pub transform: Option<Transform>
+/// Whether the coordinates of a command are absolute or relative to the
+/// current point.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Position {
+    Absolute,
+    Relative
 }
 
-pub struct CubicBezier {
+/// A single path command, named after the svg path data grammar.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    /// M / m (x, y)
+    Move(Position, (f64, f64)),
+    /// L / l (x, y)
+    Line(Position, (f64, f64)),
+    /// H / h x
+    HorizontalLine(Position, f64),
+    /// V / v y
+    VerticalLine(Position, f64),
+    /// C / c (x1, y1) (x2, y2) (x, y)
+    CubicCurve(Position, (f64, f64), (f64, f64), (f64, f64)),
+    /// S / s (x2, y2) (x, y)
+    SmoothCubicCurve(Position, (f64, f64), (f64, f64)),
+    /// Q / q (x1, y1) (x, y)
+    QuadraticCurve(Position, (f64, f64), (f64, f64)),
+    /// T / t (x, y)
+    SmoothQuadraticCurve(Position, (f64, f64)),
+    /// A / a (rx, ry) x-axis-rotation large-arc-flag sweep-flag (x, y)
+    EllipticalArc(Position, (f64, f64), f64, bool, bool, (f64, f64)),
+    /// Z
+    Close
+}
+
+/// The content of the `d` attribute of a path.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Data {
+    pub commands: Vec<Command>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Path {
+    pub data: Data,
+    pub style: Style,
+    pub attribs: Attributes,
+    pub transform: Option<Transform>,
+    pub markers: Markers
+}
+
+/// An error in path data, positioned at the offset (in characters) where it
+/// was detected.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PathError {
+    pub position: usize,
+    pub desc: String
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.desc, self.position)
+    }
+}
+
+impl Error for PathError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+fn to_int(b: bool) -> i32 { if b == true { 1 } else { 0 } }
+
+fn letter(position: Position, absolute: &'static str, relative: &'static str) -> &'static str {
+    match position {
+        Position::Absolute => absolute,
+        Position::Relative => relative
+    }
+}
+
+impl Data {
+    pub fn new() -> Data {
+        Data {
+            commands: Vec::new()
+        }
+    }
+
+    pub fn add(&mut self, command: Command) -> &mut Data {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn move_to(&mut self, x: f64, y: f64) -> &mut Data {
+        self.add(Command::Move(Position::Absolute, (x, y)))
+    }
+
+    pub fn move_by(&mut self, dx: f64, dy: f64) -> &mut Data {
+        self.add(Command::Move(Position::Relative, (dx, dy)))
+    }
+
+    pub fn line_to(&mut self, x: f64, y: f64) -> &mut Data {
+        self.add(Command::Line(Position::Absolute, (x, y)))
+    }
+
+    pub fn line_by(&mut self, dx: f64, dy: f64) -> &mut Data {
+        self.add(Command::Line(Position::Relative, (dx, dy)))
+    }
+
+    pub fn horizontal_line_to(&mut self, x: f64) -> &mut Data {
+        self.add(Command::HorizontalLine(Position::Absolute, x))
+    }
+
+    pub fn horizontal_line_by(&mut self, dx: f64) -> &mut Data {
+        self.add(Command::HorizontalLine(Position::Relative, dx))
+    }
+
+    pub fn vertical_line_to(&mut self, y: f64) -> &mut Data {
+        self.add(Command::VerticalLine(Position::Absolute, y))
+    }
+
+    pub fn vertical_line_by(&mut self, dy: f64) -> &mut Data {
+        self.add(Command::VerticalLine(Position::Relative, dy))
+    }
+
+    pub fn cubic_curve_to(&mut self,
+                          x1: f64, y1: f64,
+                          x2: f64, y2: f64,
+                          x: f64, y: f64) -> &mut Data {
+        self.add(Command::CubicCurve(Position::Absolute, (x1, y1), (x2, y2), (x, y)))
+    }
+
+    pub fn cubic_curve_by(&mut self,
+                          dx1: f64, dy1: f64,
+                          dx2: f64, dy2: f64,
+                          dx: f64, dy: f64) -> &mut Data {
+        self.add(Command::CubicCurve(Position::Relative, (dx1, dy1), (dx2, dy2), (dx, dy)))
+    }
+
+    pub fn smooth_cubic_curve_to(&mut self,
+                                 x2: f64, y2: f64,
+                                 x: f64, y: f64) -> &mut Data {
+        self.add(Command::SmoothCubicCurve(Position::Absolute, (x2, y2), (x, y)))
+    }
+
+    pub fn smooth_cubic_curve_by(&mut self,
+                                 dx2: f64, dy2: f64,
+                                 dx: f64, dy: f64) -> &mut Data {
+        self.add(Command::SmoothCubicCurve(Position::Relative, (dx2, dy2), (dx, dy)))
+    }
+
+    pub fn quadratic_curve_to(&mut self,
+                              x1: f64, y1: f64,
+                              x: f64, y: f64) -> &mut Data {
+        self.add(Command::QuadraticCurve(Position::Absolute, (x1, y1), (x, y)))
+    }
+
+    pub fn quadratic_curve_by(&mut self,
+                              dx1: f64, dy1: f64,
+                              dx: f64, dy: f64) -> &mut Data {
+        self.add(Command::QuadraticCurve(Position::Relative, (dx1, dy1), (dx, dy)))
+    }
+
+    pub fn smooth_quadratic_curve_to(&mut self, x: f64, y: f64) -> &mut Data {
+        self.add(Command::SmoothQuadraticCurve(Position::Absolute, (x, y)))
+    }
+
+    pub fn smooth_quadratic_curve_by(&mut self, dx: f64, dy: f64) -> &mut Data {
+        self.add(Command::SmoothQuadraticCurve(Position::Relative, (dx, dy)))
+    }
+
+    pub fn elliptical_arc_to(&mut self,
+                             x_radius: f64, y_radius: f64,
+                             x_axis_rotation: f64,
+                             large_arc: bool, sweep: bool,
+                             x: f64, y: f64) -> &mut Data {
+        self.add(Command::EllipticalArc(Position::Absolute, (x_radius, y_radius),
+                                        x_axis_rotation, large_arc, sweep, (x, y)))
+    }
+
+    pub fn elliptical_arc_by(&mut self,
+                             x_radius: f64, y_radius: f64,
+                             x_axis_rotation: f64,
+                             large_arc: bool, sweep: bool,
+                             dx: f64, dy: f64) -> &mut Data {
+        self.add(Command::EllipticalArc(Position::Relative, (x_radius, y_radius),
+                                        x_axis_rotation, large_arc, sweep, (dx, dy)))
+    }
+
+    pub fn close(&mut self) -> &mut Data {
+        self.add(Command::Close)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize
+}
+
+impl Parser {
+    fn error(&self, desc: &str) -> PathError {
+        PathError {
+            position: self.pos,
+            desc: desc.to_string()
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        if self.pos < self.chars.len() { Some(self.chars[self.pos]) } else { None }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() { break }
+            self.pos += 1;
+        }
+    }
+
+    // whitespace with at most one comma in between
+    fn skip_separator(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(',') {
+            self.pos += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    fn at_number(&self) -> bool {
+        match self.peek() {
+            Some(c) => c.is_digit(10) || c == '-' || c == '+' || c == '.',
+            None    => false
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !c.is_digit(10) { break }
+            self.pos += 1;
+        }
+        self.pos - start
+    }
 
+    // numbers may follow each other without separator as long as it is not
+    // ambiguous, e.g. `1.5.5` is `1.5 .5` and `1-2` is `1 -2`
+    fn number(&mut self) -> Result<f64, PathError> {
+        self.skip_separator();
+        let start = self.pos;
+        if self.peek() == Some('-') || self.peek() == Some('+') { self.pos += 1 }
+        let mut count = self.digits();
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            count += self.digits();
+        }
+        if count == 0 {
+            self.pos = start;
+            return Err(self.error("expected a number"))
+        }
+        if self.peek() == Some('e') || self.peek() == Some('E') {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if self.peek() == Some('-') || self.peek() == Some('+') { self.pos += 1 }
+            // not an exponent, e.g. the `e` of a following command
+            if self.digits() == 0 { self.pos = mantissa_end }
+        }
+        let s: String = self.chars[start..self.pos].iter().cloned().collect();
+        match s.as_slice().parse::<f64>() {
+            Ok(n)  => Ok(n),
+            Err(_) => {
+                self.pos = start;
+                Err(self.error("invalid number"))
+            }
+        }
+    }
+
+    // flags are a single `0` or `1` and need no separator, e.g. `a1 1 0 00 1 1`
+    fn flag(&mut self) -> Result<bool, PathError> {
+        self.skip_separator();
+        match self.peek() {
+            Some('0') => { self.pos += 1; Ok(false) },
+            Some('1') => { self.pos += 1; Ok(true) },
+            _         => Err(self.error("expected a flag"))
+        }
+    }
+
+    fn pair(&mut self) -> Result<(f64, f64), PathError> {
+        let x = try!(self.number());
+        let y = try!(self.number());
+        Ok((x, y))
+    }
+
+    fn arguments(&mut self, command: char, p: Position) -> Result<Command, PathError> {
+        Ok(match command {
+            'M' | 'm' => Command::Move(p, try!(self.pair())),
+            'L' | 'l' => Command::Line(p, try!(self.pair())),
+            'H' | 'h' => Command::HorizontalLine(p, try!(self.number())),
+            'V' | 'v' => Command::VerticalLine(p, try!(self.number())),
+            'C' | 'c' => {
+                let c1 = try!(self.pair());
+                let c2 = try!(self.pair());
+                Command::CubicCurve(p, c1, c2, try!(self.pair()))
+            },
+            'S' | 's' => {
+                let c2 = try!(self.pair());
+                Command::SmoothCubicCurve(p, c2, try!(self.pair()))
+            },
+            'Q' | 'q' => {
+                let c1 = try!(self.pair());
+                Command::QuadraticCurve(p, c1, try!(self.pair()))
+            },
+            'T' | 't' => Command::SmoothQuadraticCurve(p, try!(self.pair())),
+            'A' | 'a' => {
+                let radii = try!(self.pair());
+                let rotation = try!(self.number());
+                let large_arc = try!(self.flag());
+                let sweep = try!(self.flag());
+                Command::EllipticalArc(p, radii, rotation, large_arc, sweep, try!(self.pair()))
+            },
+            _         => unreachable!()
+        })
+    }
+
+    fn parse(&mut self) -> Result<Data, PathError> {
+        let mut data = Data::new();
+        self.skip_whitespace();
+        if self.peek().is_none() { return Ok(data) }
+        match self.peek() {
+            Some('M') | Some('m') => {},
+            _                     => return Err(self.error("path data must start with a move command"))
+        }
+        while let Some(c) = self.peek() {
+            if !"MmLlHhVvCcSsQqTtAaZz".contains_char(c) {
+                return Err(self.error(format!("unknown command `{}`", c).as_slice()))
+            }
+            self.pos += 1;
+            let position = if c.is_uppercase() { Position::Absolute } else { Position::Relative };
+            if c == 'Z' || c == 'z' {
+                data.commands.push(Command::Close);
+            } else {
+                data.commands.push(try!(self.arguments(c, position)));
+                // implicit repetition of the command, a move is followed by lines
+                let repeated = match c { 'M' => 'L', 'm' => 'l', c => c };
+                loop {
+                    self.skip_separator();
+                    if !self.at_number() { break }
+                    data.commands.push(try!(self.arguments(repeated, position)));
+                }
+            }
+            self.skip_whitespace();
+        }
+        Ok(data)
+    }
 }
 
-fn to_int(b: bool) -> int { if b == true { 1 } else { 0 } }
+fn add(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+fn lerp(a: (f64, f64), b: (f64, f64), t: f64) -> (f64, f64) {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+// reflection of `control` around `point`
+fn reflect(control: Option<(f64, f64)>, point: (f64, f64)) -> (f64, f64) {
+    match control {
+        Some((x, y)) => (2. * point.0 - x, 2. * point.1 - y),
+        None         => point
+    }
+}
 
+// signed angle from u to v
+fn angle(u: (f64, f64), v: (f64, f64)) -> f64 {
+    (u.0 * v.1 - u.1 * v.0).atan2(u.0 * v.0 + u.1 * v.1)
+}
+
+// Approximate an elliptical arc by cubic curves of at most 90 degrees each,
+// following the endpoint to center conversion of the svg implementation notes.
+fn arc_to_cubics(from: (f64, f64),
+                 radii: (f64, f64),
+                 x_axis_rotation: f64,
+                 large_arc: bool,
+                 sweep: bool,
+                 to: (f64, f64)) -> Vec<Command> {
+    let abs = Position::Absolute;
+    if from == to { return Vec::new() }
+    let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+    if rx == 0. || ry == 0. {
+        return vec![Command::CubicCurve(abs, lerp(from, to, 1. / 3.), lerp(from, to, 2. / 3.), to)]
+    }
+    let phi = x_axis_rotation.to_radians();
+    let (sin, cos) = (phi.sin(), phi.cos());
+    let (dx, dy) = ((from.0 - to.0) / 2., (from.1 - to.1) / 2.);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+    // scale up radii too small to join both points
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx = rx * lambda.sqrt();
+        ry = ry * lambda.sqrt();
+    }
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = (num / den).max(0.).sqrt();
+    if large_arc == sweep { coef = -coef }
+    let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+    let center = (cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.,
+                  sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.);
+    let u = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let v = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let start = angle((1., 0.), u);
+    let mut delta = angle(u, v);
+    if !sweep && delta > 0. {
+        delta = delta - 2. * PI
+    } else if sweep && delta < 0. {
+        delta = delta + 2. * PI
+    }
+    let segments = (delta.abs() / (PI / 2.)).ceil().max(1.) as usize;
+    let step = delta / segments as f64;
+    let k = 4. / 3. * (step / 4.).tan();
+    let point = |t: f64| (center.0 + rx * t.cos() * cos - ry * t.sin() * sin,
+                          center.1 + rx * t.cos() * sin + ry * t.sin() * cos);
+    let derivative = |t: f64| (-rx * t.sin() * cos - ry * t.cos() * sin,
+                               -rx * t.sin() * sin + ry * t.cos() * cos);
+    let mut curves = Vec::new();
+    for i in 0..segments {
+        let t1 = start + step * i as f64;
+        let t2 = t1 + step;
+        let (p1, p2) = (point(t1), point(t2));
+        let (d1, d2) = (derivative(t1), derivative(t2));
+        let end = if i + 1 == segments { to } else { p2 };
+        curves.push(Command::CubicCurve(abs,
+                                        (p1.0 + k * d1.0, p1.1 + k * d1.1),
+                                        (p2.0 - k * d2.0, p2.1 - k * d2.1),
+                                        end));
+    }
+    curves
+}
+
+impl Data {
+    pub fn parse(d: &str) -> Result<Data, PathError> {
+        Parser {
+            chars: d.chars().collect(),
+            pos: 0
+        }.parse()
+    }
+
+    /// The same path with every command using absolute coordinates.
+    pub fn to_absolute(&self) -> Data {
+        let abs = Position::Absolute;
+        let mut data = Data::new();
+        let mut current = (0., 0.);
+        let mut start = (0., 0.);
+        for command in self.commands.iter() {
+            let origin = match *command {
+                Command::Close                            => (0., 0.),
+                Command::Move(p, _) | Command::Line(p, _)
+                | Command::HorizontalLine(p, _) | Command::VerticalLine(p, _)
+                | Command::CubicCurve(p, _, _, _) | Command::SmoothCubicCurve(p, _, _)
+                | Command::QuadraticCurve(p, _, _) | Command::SmoothQuadraticCurve(p, _)
+                | Command::EllipticalArc(p, _, _, _, _, _) => {
+                    if p == Position::Absolute { (0., 0.) } else { current }
+                }
+            };
+            let command = match *command {
+                Command::Move(_, p)                    => Command::Move(abs, add(origin, p)),
+                Command::Line(_, p)                    => Command::Line(abs, add(origin, p)),
+                Command::HorizontalLine(_, x)          => Command::HorizontalLine(abs, origin.0 + x),
+                Command::VerticalLine(_, y)            => Command::VerticalLine(abs, origin.1 + y),
+                Command::CubicCurve(_, c1, c2, p)      => {
+                    Command::CubicCurve(abs, add(origin, c1), add(origin, c2), add(origin, p))
+                },
+                Command::SmoothCubicCurve(_, c2, p)    => {
+                    Command::SmoothCubicCurve(abs, add(origin, c2), add(origin, p))
+                },
+                Command::QuadraticCurve(_, c1, p)      => {
+                    Command::QuadraticCurve(abs, add(origin, c1), add(origin, p))
+                },
+                Command::SmoothQuadraticCurve(_, p)    => Command::SmoothQuadraticCurve(abs, add(origin, p)),
+                Command::EllipticalArc(_, r, rotation, large_arc, sweep, p) => {
+                    Command::EllipticalArc(abs, r, rotation, large_arc, sweep, add(origin, p))
+                },
+                Command::Close                         => Command::Close
+            };
+            match command {
+                Command::Move(_, p)                    => { current = p; start = p },
+                Command::HorizontalLine(_, x)          => current.0 = x,
+                Command::VerticalLine(_, y)            => current.1 = y,
+                Command::Line(_, p) | Command::CubicCurve(_, _, _, p)
+                | Command::SmoothCubicCurve(_, _, p) | Command::QuadraticCurve(_, _, p)
+                | Command::SmoothQuadraticCurve(_, p)
+                | Command::EllipticalArc(_, _, _, _, _, p) => current = p,
+                Command::Close                         => current = start
+            }
+            data.commands.push(command);
+        }
+        data
+    }
+
+    /// The canonical form of the path: absolute coordinates, only moves,
+    /// lines, cubic and quadratic curves with explicit control points, arcs
+    /// and close commands.
+    pub fn normalize(&self) -> Data {
+        let abs = Position::Absolute;
+        let mut data = Data::new();
+        let mut current = (0., 0.);
+        let mut start = (0., 0.);
+        let mut last_cubic = None;
+        let mut last_quadratic = None;
+        for command in self.to_absolute().commands.into_iter() {
+            let (mut cubic, mut quadratic) = (None, None);
+            let command = match command {
+                Command::HorizontalLine(_, x)         => Command::Line(abs, (x, current.1)),
+                Command::VerticalLine(_, y)           => Command::Line(abs, (current.0, y)),
+                Command::CubicCurve(_, c1, c2, p)     => {
+                    cubic = Some(c2);
+                    Command::CubicCurve(abs, c1, c2, p)
+                },
+                Command::SmoothCubicCurve(_, c2, p)   => {
+                    cubic = Some(c2);
+                    Command::CubicCurve(abs, reflect(last_cubic, current), c2, p)
+                },
+                Command::QuadraticCurve(_, c1, p)     => {
+                    quadratic = Some(c1);
+                    Command::QuadraticCurve(abs, c1, p)
+                },
+                Command::SmoothQuadraticCurve(_, p)   => {
+                    let c1 = reflect(last_quadratic, current);
+                    quadratic = Some(c1);
+                    Command::QuadraticCurve(abs, c1, p)
+                },
+                command                               => command
+            };
+            match command {
+                Command::Move(_, p)                   => { current = p; start = p },
+                Command::Line(_, p) | Command::CubicCurve(_, _, _, p)
+                | Command::QuadraticCurve(_, _, p)
+                | Command::EllipticalArc(_, _, _, _, _, p) => current = p,
+                Command::Close                        => current = start,
+                _                                     => unreachable!()
+            }
+            last_cubic = cubic;
+            last_quadratic = quadratic;
+            data.commands.push(command);
+        }
+        data
+    }
+
+    /// The normalized path with every line, quadratic curve and arc converted
+    /// into cubic curves.
+    pub fn to_cubic(&self) -> Data {
+        let abs = Position::Absolute;
+        let mut data = Data::new();
+        let mut current = (0., 0.);
+        let mut start = (0., 0.);
+        for command in self.normalize().commands.into_iter() {
+            match command {
+                Command::Move(_, p)                     => {
+                    data.commands.push(command.clone());
+                    current = p;
+                    start = p;
+                },
+                Command::Line(_, p)                     => {
+                    data.commands.push(Command::CubicCurve(abs, lerp(current, p, 1. / 3.),
+                                                           lerp(current, p, 2. / 3.), p));
+                    current = p;
+                },
+                Command::CubicCurve(_, _, _, p)         => {
+                    data.commands.push(command.clone());
+                    current = p;
+                },
+                Command::QuadraticCurve(_, c, p)        => {
+                    data.commands.push(Command::CubicCurve(abs, lerp(current, c, 2. / 3.),
+                                                           lerp(p, c, 2. / 3.), p));
+                    current = p;
+                },
+                Command::EllipticalArc(_, r, rotation, large_arc, sweep, p) => {
+                    data.commands.extend(arc_to_cubics(current, r, rotation,
+                                                       large_arc, sweep, p).into_iter());
+                    current = p;
+                },
+                Command::Close                          => {
+                    data.commands.push(Command::Close);
+                    current = start;
+                },
+                _                                       => unreachable!()
+            }
+        }
+        data
+    }
+}
+
+impl FromStr for Data {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Data, PathError> {
+        Data::parse(s)
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pair = |(x, y): (f64, f64)| format!("{},{}", number(x), number(y));
+        match *self {
+            Command::Move(p, end) => {
+                write!(f, "{}{}", letter(p, "M", "m"), pair(end))
+            },
+            Command::Line(p, end) => {
+                write!(f, "{}{}", letter(p, "L", "l"), pair(end))
+            },
+            Command::HorizontalLine(p, x) => {
+                write!(f, "{}{}", letter(p, "H", "h"), number(x))
+            },
+            Command::VerticalLine(p, y) => {
+                write!(f, "{}{}", letter(p, "V", "v"), number(y))
+            },
+            Command::CubicCurve(p, c1, c2, end) => {
+                write!(f, "{}{} {} {}", letter(p, "C", "c"), pair(c1), pair(c2), pair(end))
+            },
+            Command::SmoothCubicCurve(p, c2, end) => {
+                write!(f, "{}{} {}", letter(p, "S", "s"), pair(c2), pair(end))
+            },
+            Command::QuadraticCurve(p, c1, end) => {
+                write!(f, "{}{} {}", letter(p, "Q", "q"), pair(c1), pair(end))
+            },
+            Command::SmoothQuadraticCurve(p, end) => {
+                write!(f, "{}{}", letter(p, "T", "t"), pair(end))
+            },
+            Command::EllipticalArc(p, radii, rotation, large_arc, sweep, end) => {
+                write!(f, "{}{} {} {},{} {}", letter(p, "A", "a"), pair(radii), number(rotation),
+                       to_int(large_arc), to_int(sweep), pair(end))
+            },
+            Command::Close => write!(f, "Z")
+        }
+    }
+}
+
+impl fmt::Display for Data {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, command) in self.commands.iter().enumerate() {
+            if i != 0 { try!(write!(f, " ")) }
+            try!(write!(f, "{}", command));
+        }
+        Ok(())
+    }
+}
+
+impl SVGEntity for Path {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("path");
+        e.set_attrib("d", &self.data);
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        self.markers.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
+    }
+}
@@ -0,0 +1,128 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Pattern paint servers, whose content is drawn with the shape API of
+//! `Container`.
+
+use std::convert::Into;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::{insert_attribs, number};
+use container::Container;
+use defs::{Handle, Units};
+use document::Element;
+use length::Length;
+use transform::Transform;
+use viewport::ViewBox;
+use SVGEntity;
+
+/// A `<pattern>` tile repeated to fill or stroke a shape, e.g. for hatching.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pattern {
+    pub x: f64,
+    pub y: f64,
+    pub width: Length,
+    pub height: Length,
+    pub units: Option<Units>,
+    pub content_units: Option<Units>,
+    pub transform: Option<Transform>,
+    pub view_box: Option<ViewBox>,
+    pub href: Option<Handle>,
+    pub attribs: Attributes,
+    pub children: Vec<Element>
+}
+
+impl Pattern {
+    /// An empty tile of `width` by `height` at (`x`, `y`).
+    pub fn new<L: Into<Length>>(x: f64, y: f64, width: L, height: L) -> Pattern {
+        Pattern {
+            x: x,
+            y: y,
+            width: width.into(),
+            height: height.into(),
+            units: None,
+            content_units: None,
+            transform: None,
+            view_box: None,
+            href: None,
+            attribs: Attributes::new(),
+            children: Vec::new()
+        }
+    }
+
+    /// Value of `patternUnits`, `objectBoundingBox` by default.
+    pub fn units(&mut self, units: Units) -> &mut Pattern {
+        self.units = Some(units);
+        self
+    }
+
+    /// Value of `patternContentUnits`, `userSpaceOnUse` by default.
+    pub fn content_units(&mut self, units: Units) -> &mut Pattern {
+        self.content_units = Some(units);
+        self
+    }
+
+    pub fn transform(&mut self, transform: Transform) -> &mut Pattern {
+        self.transform = Some(transform);
+        self
+    }
+
+    pub fn view_box(&mut self, view_box: ViewBox) -> &mut Pattern {
+        self.view_box = Some(view_box);
+        self
+    }
+
+    /// Take the content and the attributes not set here from another pattern.
+    pub fn href(&mut self, pattern: &Handle) -> &mut Pattern {
+        self.href = Some(pattern.clone());
+        self
+    }
+}
+
+impl Container for Pattern {
+    fn add_element(&mut self, element: Element) {
+        self.children.push(element)
+    }
+}
+
+impl SVGEntity for Pattern {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("pattern");
+        e.set_attrib("x", number(self.x));
+        e.set_attrib("y", number(self.y));
+        e.set_attrib("width", self.width);
+        e.set_attrib("height", self.height);
+        if let Some(ref u) = self.units { e.set_attrib("patternUnits", u) }
+        if let Some(ref u) = self.content_units { e.set_attrib("patternContentUnits", u) }
+        match self.transform {
+            Some(ref t) if !t.is_empty() => e.set_attrib("patternTransform", t.value()),
+            _                            => {/* nothing to do */}
+        }
+        if let Some(v) = self.view_box { e.set_attrib("viewBox", v) }
+        if let Some(ref h) = self.href { e.set_attrib("xlink:href", h.href()) }
+        insert_attribs(&mut e, &self.attribs);
+        for child in self.children.iter() {
+            e.add_child(child.clone());
+        }
+        e
+    }
+}
@@ -19,167 +19,203 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use std::fmt::Debug;
 use std::vec::Vec;
-use std::collections::HashMap;
-use std::num::Int;
 
-use common::{insert_attribs, insert_transform, finalize};
+use attribs::Attributes;
+use common::{insert_attribs, insert_transform, number};
+use document::Element;
+use length::Length;
+use marker::Markers;
+use style::Style;
 use transform::Transform;
 use SVGEntity;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Circle {
-    pub x: i32,
-    pub y: i32,
-    pub radius: u32,
-    pub attribs: HashMap<String, String>,
+    pub x: f64,
+    pub y: f64,
+    pub radius: Length,
+    pub style: Style,
+    pub attribs: Attributes,
     pub transform: Option<Transform>
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Ellipse {
-    pub x: i32,
-    pub y: i32,
-    pub x_radius: u32,
-    pub y_radius: u32,
-    pub attribs: HashMap<String, String>,
+    pub x: f64,
+    pub y: f64,
+    pub x_radius: Length,
+    pub y_radius: Length,
+    pub style: Style,
+    pub attribs: Attributes,
     pub transform: Option<Transform>
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Line {
-    pub x1: i32,
-    pub y1: i32,
-    pub x2: i32,
-    pub y2: i32,
-    pub attribs: HashMap<String, String>,
-    pub transform: Option<Transform>
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+    pub style: Style,
+    pub attribs: Attributes,
+    pub transform: Option<Transform>,
+    pub markers: Markers
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct RoundedRect {
-    pub x: i32,
-    pub y: i32,
-    pub width: i32,
-    pub height: i32,
-    pub x_round: u32,
-    pub y_round: u32,
-    pub attribs: HashMap<String, String>,
+    pub x: f64,
+    pub y: f64,
+    pub width: Length,
+    pub height: Length,
+    pub x_round: Length,
+    pub y_round: Length,
+    pub style: Style,
+    pub attribs: Attributes,
     pub transform: Option<Transform>
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Rect {
-    pub x: i32,
-    pub y: i32,
-    pub width: i32,
-    pub height: i32,
-    pub attribs: HashMap<String, String>,
+    pub x: f64,
+    pub y: f64,
+    pub width: Length,
+    pub height: Length,
+    pub style: Style,
+    pub attribs: Attributes,
     pub transform: Option<Transform>
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub struct PolyLine<T> {
-    pub points: Vec<(T, T)>,
-    pub attribs: HashMap<String, String>,
-    pub transform: Option<Transform>
+pub struct PolyLine {
+    pub points: Vec<(f64, f64)>,
+    pub style: Style,
+    pub attribs: Attributes,
+    pub transform: Option<Transform>,
+    pub markers: Markers
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub struct Polygon<T> {
-    pub points: Vec<(T, T)>,
-    pub attribs: HashMap<String, String>,
-    pub transform: Option<Transform>
+pub struct Polygon {
+    pub points: Vec<(f64, f64)>,
+    pub style: Style,
+    pub attribs: Attributes,
+    pub transform: Option<Transform>,
+    pub markers: Markers
 }
 
-impl<T: Int + Debug> PolyLine<T> {
-    pub fn add_point(&mut self, x: T, y: T) {
+impl PolyLine {
+    pub fn add_point(&mut self, x: f64, y: f64) {
         self.points.push((x, y))
     }
 }
 
-impl<T: Int + Debug> Polygon<T> {
-    pub fn add_point(&mut self, x: T, y: T) {
+impl Polygon {
+    pub fn add_point(&mut self, x: f64, y: f64) {
         self.points.push((x, y))
     }
 }
 
-fn get_points<T: Int + Debug>(points: &Vec<(T, T)>) -> String {
-    let mut p: String = String::from_str("points=\"");
-    for &(ref x, ref y) in points.iter() {
-        p.push_str(format!("{:?},{:?} ", x, y).as_slice())
-    }
-    p.push_str("\"");
-    p
+fn get_points(points: &Vec<(f64, f64)>) -> String {
+    let p: Vec<String> = points.iter()
+                               .map(|&(x, y)| format!("{},{}", number(x), number(y)))
+                               .collect();
+    p.connect(" ")
 }
 
 impl SVGEntity for Circle {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<circle cx=\"{:?}\" cy=\"{:?}\" r=\"{:?}\"",
-                           self.x, self.y, self.radius).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        finalize(o)
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("circle");
+        e.set_attrib("cx", number(self.x));
+        e.set_attrib("cy", number(self.y));
+        e.set_attrib("r", self.radius);
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
     }
 }
 
-impl<T: Int + Debug> SVGEntity for PolyLine<T> {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<polyline {:?}", get_points(&self.points)).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        finalize(o)
+impl SVGEntity for PolyLine {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("polyline");
+        e.set_attrib("points", get_points(&self.points));
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        self.markers.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
     }
 }
 
-impl<T: Int + Debug> SVGEntity for Polygon<T> {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<polygon {:?}", get_points(&self.points)).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        finalize(o)
+impl SVGEntity for Polygon {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("polygon");
+        e.set_attrib("points", get_points(&self.points));
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        self.markers.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
     }
 }
 
 impl SVGEntity for Line {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<line x1=\"{:?}\" y1=\"{:?}\" x2=\"{:?}\" y2=\"{:?}\"",
-                           self.x1, self.y1, self.x2, self.y2).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        finalize(o)
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("line");
+        e.set_attrib("x1", number(self.x1));
+        e.set_attrib("y1", number(self.y1));
+        e.set_attrib("x2", number(self.x2));
+        e.set_attrib("y2", number(self.y2));
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        self.markers.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
     }
 }
 
 impl SVGEntity for Ellipse {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<ellipse cx=\"{:?}\" cy=\"{:?}\" rx=\"{:?}\" ry=\"{:?}\"",
-                           self.x, self.y, self.x_radius, self.y_radius).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        finalize(o)
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("ellipse");
+        e.set_attrib("cx", number(self.x));
+        e.set_attrib("cy", number(self.y));
+        e.set_attrib("rx", self.x_radius);
+        e.set_attrib("ry", self.y_radius);
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
     }
 }
 
 impl SVGEntity for Rect {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<rect x=\"{:?}\" y=\"{:?}\" width=\"{:?}\" height=\"{:?}\"",
-                           self.x, self.y, self.width, self.height).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        finalize(o)
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("rect");
+        e.set_attrib("x", number(self.x));
+        e.set_attrib("y", number(self.y));
+        e.set_attrib("width", self.width);
+        e.set_attrib("height", self.height);
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
     }
 }
 
 impl SVGEntity for RoundedRect {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<rect x=\"{:?}\" y=\"{:?}\" width=\"{:?}\" height=\"{:?}\" \
-                           rx=\"{:?}\" ry=\"{:?}\"",
-                           self.x, self.y, self.width, self.height,
-                           self.x_round, self.y_round).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        finalize(o)
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("rect");
+        e.set_attrib("x", number(self.x));
+        e.set_attrib("y", number(self.y));
+        e.set_attrib("width", self.width);
+        e.set_attrib("height", self.height);
+        e.set_attrib("rx", self.x_round);
+        e.set_attrib("ry", self.y_round);
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
     }
 }
@@ -0,0 +1,263 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Streaming output: every element is written as soon as it is added, so
+//! images of any size are generated in constant memory.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::{Precision, NumberFormat};
+use container::Container;
+use defs::{Handle, IdGenerator};
+use document::{Element, Node};
+use viewport::Viewport;
+use xml::{Serializer, Format};
+use epilog;
+use prolog;
+use root_attribs;
+use text_element;
+use ContainerKind;
+use SVGEntity;
+
+/// Writes an image straight to `W`. Unlike `SVG`, elements cannot be found or
+/// modified once added, and definitions are written where they are defined.
+pub struct StreamWriter<W: Write> {
+    output: W,
+    // kinds and names of the containers opened and not closed yet, innermost
+    // last
+    open: Vec<(ContainerKind, String)>,
+    format: Format,
+    numbers: NumberFormat,
+    // ids of the elements written so far, so generated ids do not clash
+    // with them
+    written_ids: HashSet<String>,
+    ids: IdGenerator,
+    // first error of the shape API of `Container`, reported by `finish`
+    error: Option<io::Error>
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Start an image on `output`, writing its head right away.
+    pub fn new(output: W,
+               viewport: &Viewport,
+               standalone: bool) -> io::Result<StreamWriter<W>> {
+        StreamWriter::with_format(output, viewport, standalone, Format::Plain, NumberFormat::new())
+    }
+
+    /// Like `new`, with the output laid out according to `format` and its
+    /// numbers written according to `numbers`, the head being written right
+    /// away. The minified format always drops the leading zero of fractional
+    /// numbers.
+    pub fn with_format(output: W,
+                       viewport: &Viewport,
+                       standalone: bool,
+                       format: Format,
+                       numbers: NumberFormat) -> io::Result<StreamWriter<W>> {
+        let mut writer = StreamWriter {
+            output: output,
+            open: Vec::new(),
+            format: format,
+            numbers: numbers,
+            written_ids: HashSet::new(),
+            ids: IdGenerator::new(),
+            error: None
+        };
+        let attribs = root_attribs(viewport, &Attributes::new());
+        let head = prolog(standalone, &attribs, format, writer.number_format());
+        try!(writer.output.write_all(head.as_bytes()));
+        Ok(writer)
+    }
+
+    /// Rounding of every number written from now on.
+    pub fn precision(&mut self, precision: Precision) {
+        self.numbers.precision = precision;
+    }
+
+    /// Write the fractional numbers without their leading zero from now on.
+    pub fn short_numbers(&mut self, short: bool) {
+        self.numbers.short = short;
+    }
+
+    pub fn title(&mut self, text: &str) -> io::Result<()> {
+        self.write_element(&text_element("title", text))
+    }
+
+    pub fn desc(&mut self, text: &str) -> io::Result<()> {
+        self.write_element(&text_element("desc", text))
+    }
+
+    pub fn write_element(&mut self, element: &Element) -> io::Result<()> {
+        try!(self.check());
+        let mut s = self.serializer();
+        s.element(element);
+        try!(self.output.write_all(s.as_slice().as_bytes()));
+        self.record_ids(element);
+        Ok(())
+    }
+
+    // remember the ids of `element` and of its descendants
+    fn record_ids(&mut self, element: &Element) {
+        if let Some(id) = element.id() { self.written_ids.insert(id.to_string()); }
+        for child in element.children.iter() {
+            if let Node::Element(ref e) = *child { self.record_ids(e) }
+        }
+    }
+
+    pub fn write<T: SVGEntity>(&mut self, entity: &T) -> io::Result<()> {
+        self.write_element(&entity.to_element())
+    }
+
+    /// Write `definition` in its own `<defs>` element and return the handle
+    /// used to reference it. An id not used by the elements written so far is
+    /// generated when the definition has none, an id which is already used
+    /// is an error.
+    pub fn define<T: SVGEntity>(&mut self, definition: &T) -> io::Result<Handle> {
+        let mut element = definition.to_element();
+        match element.id().map(|id| id.to_string()) {
+            Some(ref id) if self.written_ids.contains(id) => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "duplicate definition id"))
+            },
+            Some(_)                                       => {/* a free id */},
+            None                                          => {
+                let mut id = self.ids.next(element.name.as_slice());
+                while self.written_ids.contains(&id) {
+                    id = self.ids.next(element.name.as_slice());
+                }
+                element.set_attrib("id", id);
+            }
+        }
+        let handle = Handle::new(element.id().unwrap());
+        let mut defs = Element::new("defs");
+        defs.add_child(element);
+        try!(self.write_element(&defs));
+        Ok(handle)
+    }
+
+    /// Open `element` as a container, the elements written until the
+    /// matching `end` become its children. The children `element` already
+    /// has are not written.
+    pub fn begin(&mut self, element: &Element) -> io::Result<()> {
+        self.begin_kind(ContainerKind::Element, element)
+    }
+
+    fn begin_kind(&mut self, kind: ContainerKind, element: &Element) -> io::Result<()> {
+        try!(self.check());
+        let mut s = self.serializer();
+        s.indent();
+        s.start_tag(element.name.as_slice(), &element.attribs, false);
+        s.newline();
+        try!(self.output.write_all(s.as_slice().as_bytes()));
+        if let Some(id) = element.id() { self.written_ids.insert(id.to_string()); }
+        self.open.push((kind, element.name.clone()));
+        Ok(())
+    }
+
+    /// Open a group with the given attributes.
+    pub fn g_begin(&mut self, attribs: &Attributes) -> io::Result<()> {
+        let mut g = Element::new("g");
+        g.attribs.extend(attribs);
+        self.begin_kind(ContainerKind::Group, &g)
+    }
+
+    /// Close the innermost container, whatever its kind. As a streamed image
+    /// cannot be fixed afterwards, closing a container when none is open is
+    /// an `InvalidInput` error.
+    pub fn end(&mut self) -> io::Result<()> {
+        try!(self.check());
+        match self.open.pop() {
+            Some((_, name)) => {
+                let mut s = self.serializer();
+                s.indent();
+                s.end_tag(name.as_slice());
+                s.newline();
+                self.output.write_all(s.as_slice().as_bytes())
+            },
+            None            => {
+                Err(io::Error::new(io::ErrorKind::InvalidInput, "no open container to close"))
+            }
+        }
+    }
+
+    /// Close the innermost container, which must be a group opened by
+    /// `g_begin`, an `InvalidInput` error otherwise.
+    pub fn g_end(&mut self) -> io::Result<()> {
+        match self.open.last() {
+            Some(&(ContainerKind::Group, _)) => {},
+            _                                => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "no open group to close"))
+            }
+        }
+        self.end()
+    }
+
+    // Nothing is written once the shape API met an error, the output may be
+    // in an inconsistent state.
+    fn check(&self) -> io::Result<()> {
+        match self.error {
+            Some(ref e) => Err(io::Error::new(e.kind(), "an earlier write failed")),
+            None        => Ok(())
+        }
+    }
+
+    // the format of the numbers written from now on
+    fn number_format(&self) -> NumberFormat {
+        let mut numbers = self.numbers;
+        numbers.short = numbers.short || self.format == Format::Minified;
+        numbers
+    }
+
+    // A serializer for the elements nested in the open containers.
+    fn serializer(&self) -> Serializer {
+        let mut s = Serializer::with_format(self.format, self.open.len() + 1);
+        s.number_format(self.number_format());
+        s
+    }
+
+    /// Close the open containers and the image, flush and give the output
+    /// back. Fails with the first error met by the shape API if any.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error.take() {
+            Some(e) => return Err(e),
+            None    => {/* nothing went wrong */}
+        }
+        while !self.open.is_empty() {
+            try!(self.end());
+        }
+        try!(self.output.write_all(epilog(self.format).as_bytes()));
+        try!(self.output.flush());
+        Ok(self.output)
+    }
+}
+
+impl<W: Write> Container for StreamWriter<W> {
+    /// Write `element`. As the shape API cannot fail on output errors, the
+    /// first one is kept and returned by `finish`, and nothing more is
+    /// written after it.
+    fn add_element(&mut self, element: Element) {
+        if self.error.is_some() { return }
+        if let Err(e) = self.write_element(&element) {
+            self.error = Some(e)
+        }
+    }
+}
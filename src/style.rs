@@ -0,0 +1,534 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Typed presentation attributes.
+//!
+//! Values are validated when they are created, so a `Style` always produces
+//! valid attributes.
+
+use std::error::Error;
+use std::fmt;
+use std::num::Float;
+use std::vec::Vec;
+
+use color::Color;
+use common::number;
+use defs::Handle;
+use document::Element;
+use length::Length;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StyleError {
+    pub desc: String
+}
+
+impl fmt::Display for StyleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.desc)
+    }
+}
+
+impl Error for StyleError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+fn error<T>(desc: String) -> Result<T, StyleError> {
+    Err(StyleError { desc: desc })
+}
+
+/// Value of `fill` and `stroke`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Paint {
+    None,
+    CurrentColor,
+    Color(Color),
+    /// reference to a paint server, with an optional fallback color
+    Url(String, Option<Color>)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StrokeWidth(Length);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MiterLimit(f64);
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DashArray(Vec<f64>);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DashOffset(f64);
+
+/// Value of `opacity`, `fill-opacity` and `stroke-opacity`, in `[0, 1]`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Opacity(f64);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Visibility {
+    Visible,
+    Hidden,
+    Collapse
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FontFamily(Vec<String>);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FontSize(f64);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FontWeight {
+    Normal,
+    Bold,
+    Bolder,
+    Lighter,
+    /// one of 100, 200, ..., 900
+    Weight(u16)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique
+}
+
+impl Paint {
+    /// A color paint parsed from any of the forms accepted by `Color::parse`.
+    pub fn color(color: &str) -> Result<Paint, StyleError> {
+        match Color::parse(color) {
+            Ok(c)  => Ok(Paint::Color(c)),
+            Err(e) => error(e.desc)
+        }
+    }
+
+    /// A reference to the paint server with the given id.
+    pub fn url(id: &str) -> Paint {
+        Paint::Url(id.to_string(), None)
+    }
+
+    /// The paint server registered with `SVG::define`, e.g. a gradient.
+    pub fn server(handle: &Handle) -> Paint {
+        Paint::Url(handle.id().to_string(), None)
+    }
+}
+
+impl StrokeWidth {
+    pub fn new(width: Length) -> Result<StrokeWidth, StyleError> {
+        if width.value >= 0. && width.value.is_finite() {
+            Ok(StrokeWidth(width))
+        } else {
+            error(format!("invalid stroke width {}", width))
+        }
+    }
+
+    pub fn get(&self) -> Length { self.0 }
+}
+
+impl MiterLimit {
+    pub fn new(limit: f64) -> Result<MiterLimit, StyleError> {
+        if limit >= 1. && limit.is_finite() {
+            Ok(MiterLimit(limit))
+        } else {
+            error(format!("invalid miter limit {}, it must be at least 1", limit))
+        }
+    }
+
+    pub fn get(&self) -> f64 { self.0 }
+}
+
+impl DashArray {
+    pub fn new(dashes: Vec<f64>) -> Result<DashArray, StyleError> {
+        if dashes.iter().any(|d| *d < 0. || !d.is_finite()) {
+            error(format!("invalid dash array {:?}, lengths must be positive", dashes))
+        } else {
+            Ok(DashArray(dashes))
+        }
+    }
+
+    pub fn get(&self) -> &Vec<f64> { &self.0 }
+}
+
+impl DashOffset {
+    pub fn new(offset: f64) -> Result<DashOffset, StyleError> {
+        if offset.is_finite() {
+            Ok(DashOffset(offset))
+        } else {
+            error(format!("invalid dash offset {}", offset))
+        }
+    }
+
+    pub fn get(&self) -> f64 { self.0 }
+}
+
+impl Opacity {
+    pub fn new(opacity: f64) -> Result<Opacity, StyleError> {
+        if opacity >= 0. && opacity <= 1. {
+            Ok(Opacity(opacity))
+        } else {
+            error(format!("invalid opacity {}, it must be between 0 and 1", opacity))
+        }
+    }
+
+    pub fn get(&self) -> f64 { self.0 }
+}
+
+impl FontFamily {
+    pub fn new(families: Vec<&str>) -> Result<FontFamily, StyleError> {
+        if families.is_empty() {
+            return error("empty font family".to_string())
+        }
+        let mut names = Vec::new();
+        for family in families.iter() {
+            let family = family.trim();
+            if family.is_empty() || family.contains_char('"') || family.contains_char('\'') {
+                return error(format!("invalid font family `{}`", family))
+            }
+            names.push(family.to_string());
+        }
+        Ok(FontFamily(names))
+    }
+
+    pub fn get(&self) -> &Vec<String> { &self.0 }
+}
+
+impl FontSize {
+    pub fn new(size: f64) -> Result<FontSize, StyleError> {
+        if size > 0. && size.is_finite() {
+            Ok(FontSize(size))
+        } else {
+            error(format!("invalid font size {}", size))
+        }
+    }
+
+    pub fn get(&self) -> f64 { self.0 }
+}
+
+impl FontWeight {
+    pub fn numeric(weight: u16) -> Result<FontWeight, StyleError> {
+        if weight >= 100 && weight <= 900 && weight % 100 == 0 {
+            Ok(FontWeight::Weight(weight))
+        } else {
+            error(format!("invalid font weight {}", weight))
+        }
+    }
+}
+
+impl fmt::Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Paint::None                         => write!(f, "none"),
+            Paint::CurrentColor                 => write!(f, "currentColor"),
+            Paint::Color(ref c)                 => write!(f, "{}", c),
+            Paint::Url(ref id, None)            => write!(f, "url(#{})", id),
+            Paint::Url(ref id, Some(ref c))     => write!(f, "url(#{}) {}", id, c)
+        }
+    }
+}
+
+impl fmt::Display for LineCap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            LineCap::Butt   => "butt",
+            LineCap::Round  => "round",
+            LineCap::Square => "square"
+        })
+    }
+}
+
+impl fmt::Display for LineJoin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel"
+        })
+    }
+}
+
+impl fmt::Display for StrokeWidth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for DashArray {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() { return write!(f, "none") }
+        let dashes: Vec<String> = self.0.iter().map(|d| number(*d)).collect();
+        write!(f, "{}", dashes.connect(" "))
+    }
+}
+
+impl fmt::Display for FillRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            FillRule::NonZero => "nonzero",
+            FillRule::EvenOdd => "evenodd"
+        })
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            Visibility::Visible  => "visible",
+            Visibility::Hidden   => "hidden",
+            Visibility::Collapse => "collapse"
+        })
+    }
+}
+
+impl fmt::Display for FontFamily {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // quote the names which are not a single identifier
+        let names: Vec<String> = self.0.iter().map(|name| {
+            if name.as_slice().chars().all(|c| c.is_alphanumeric() || c == '-') {
+                name.clone()
+            } else {
+                format!("'{}'", name)
+            }
+        }).collect();
+        write!(f, "{}", names.connect(", "))
+    }
+}
+
+impl fmt::Display for FontWeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FontWeight::Normal      => write!(f, "normal"),
+            FontWeight::Bold        => write!(f, "bold"),
+            FontWeight::Bolder      => write!(f, "bolder"),
+            FontWeight::Lighter     => write!(f, "lighter"),
+            FontWeight::Weight(w)   => write!(f, "{}", w)
+        }
+    }
+}
+
+impl fmt::Display for FontStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            FontStyle::Normal  => "normal",
+            FontStyle::Italic  => "italic",
+            FontStyle::Oblique => "oblique"
+        })
+    }
+}
+
+/// The presentation attributes of an entity. Unset properties are not
+/// written and keep their inherited value.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Style {
+    pub fill: Option<Paint>,
+    pub fill_opacity: Option<Opacity>,
+    pub fill_rule: Option<FillRule>,
+    pub stroke: Option<Paint>,
+    pub stroke_width: Option<StrokeWidth>,
+    pub stroke_linecap: Option<LineCap>,
+    pub stroke_linejoin: Option<LineJoin>,
+    pub stroke_miterlimit: Option<MiterLimit>,
+    pub stroke_dasharray: Option<DashArray>,
+    pub stroke_dashoffset: Option<DashOffset>,
+    pub stroke_opacity: Option<Opacity>,
+    pub opacity: Option<Opacity>,
+    pub visibility: Option<Visibility>,
+    pub font_family: Option<FontFamily>,
+    pub font_size: Option<FontSize>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub clip_path: Option<Handle>,
+    pub mask: Option<Handle>,
+    pub filter: Option<Handle>
+}
+
+impl Style {
+    pub fn new() -> Style {
+        Default::default()
+    }
+
+    pub fn fill(&mut self, paint: Paint) -> &mut Style {
+        self.fill = Some(paint);
+        self
+    }
+
+    pub fn fill_opacity(&mut self, opacity: Opacity) -> &mut Style {
+        self.fill_opacity = Some(opacity);
+        self
+    }
+
+    pub fn fill_rule(&mut self, rule: FillRule) -> &mut Style {
+        self.fill_rule = Some(rule);
+        self
+    }
+
+    pub fn stroke(&mut self, paint: Paint) -> &mut Style {
+        self.stroke = Some(paint);
+        self
+    }
+
+    pub fn stroke_width(&mut self, width: StrokeWidth) -> &mut Style {
+        self.stroke_width = Some(width);
+        self
+    }
+
+    pub fn stroke_linecap(&mut self, cap: LineCap) -> &mut Style {
+        self.stroke_linecap = Some(cap);
+        self
+    }
+
+    pub fn stroke_linejoin(&mut self, join: LineJoin) -> &mut Style {
+        self.stroke_linejoin = Some(join);
+        self
+    }
+
+    pub fn stroke_miterlimit(&mut self, limit: MiterLimit) -> &mut Style {
+        self.stroke_miterlimit = Some(limit);
+        self
+    }
+
+    pub fn stroke_dasharray(&mut self, dashes: DashArray) -> &mut Style {
+        self.stroke_dasharray = Some(dashes);
+        self
+    }
+
+    pub fn stroke_dashoffset(&mut self, offset: DashOffset) -> &mut Style {
+        self.stroke_dashoffset = Some(offset);
+        self
+    }
+
+    pub fn stroke_opacity(&mut self, opacity: Opacity) -> &mut Style {
+        self.stroke_opacity = Some(opacity);
+        self
+    }
+
+    pub fn opacity(&mut self, opacity: Opacity) -> &mut Style {
+        self.opacity = Some(opacity);
+        self
+    }
+
+    pub fn visibility(&mut self, visibility: Visibility) -> &mut Style {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    pub fn font_family(&mut self, family: FontFamily) -> &mut Style {
+        self.font_family = Some(family);
+        self
+    }
+
+    pub fn font_size(&mut self, size: FontSize) -> &mut Style {
+        self.font_size = Some(size);
+        self
+    }
+
+    pub fn font_weight(&mut self, weight: FontWeight) -> &mut Style {
+        self.font_weight = Some(weight);
+        self
+    }
+
+    pub fn font_style(&mut self, style: FontStyle) -> &mut Style {
+        self.font_style = Some(style);
+        self
+    }
+
+    /// Clip to the `ClipPath` registered with `SVG::define`.
+    pub fn clip_path(&mut self, clip_path: &Handle) -> &mut Style {
+        self.clip_path = Some(clip_path.clone());
+        self
+    }
+
+    /// Mask with the `Mask` registered with `SVG::define`.
+    pub fn mask(&mut self, mask: &Handle) -> &mut Style {
+        self.mask = Some(mask.clone());
+        self
+    }
+
+    /// Apply the `Filter` registered with `SVG::define`.
+    pub fn filter(&mut self, filter: &Handle) -> &mut Style {
+        self.filter = Some(filter.clone());
+        self
+    }
+
+    /// Write the properties which are set as attributes of `element`.
+    pub fn apply(&self, element: &mut Element) {
+        macro_rules! set {
+            ($name:expr, $value:expr) => (
+                match $value {
+                    Some(ref v) => element.set_attrib($name, v),
+                    None        => {/* not set */}
+                }
+            );
+            ($name:expr, $value:expr, number) => (
+                match $value {
+                    Some(ref v) => element.set_attrib($name, number(v.get())),
+                    None        => {/* not set */}
+                }
+            );
+            ($name:expr, $value:expr, url) => (
+                match $value {
+                    Some(ref v) => element.set_attrib($name, v.url()),
+                    None        => {/* not set */}
+                }
+            )
+        }
+        set!("fill", self.fill);
+        set!("fill-opacity", self.fill_opacity, number);
+        set!("fill-rule", self.fill_rule);
+        set!("stroke", self.stroke);
+        set!("stroke-width", self.stroke_width);
+        set!("stroke-linecap", self.stroke_linecap);
+        set!("stroke-linejoin", self.stroke_linejoin);
+        set!("stroke-miterlimit", self.stroke_miterlimit, number);
+        set!("stroke-dasharray", self.stroke_dasharray);
+        set!("stroke-dashoffset", self.stroke_dashoffset, number);
+        set!("stroke-opacity", self.stroke_opacity, number);
+        set!("opacity", self.opacity, number);
+        set!("visibility", self.visibility);
+        set!("font-family", self.font_family);
+        set!("font-size", self.font_size, number);
+        set!("font-weight", self.font_weight);
+        set!("font-style", self.font_style);
+        set!("clip-path", self.clip_path, url);
+        set!("mask", self.mask, url);
+        set!("filter", self.filter, url);
+    }
+}
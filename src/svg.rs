@@ -25,90 +25,341 @@
 // #![warn(missing_doc)]
 #![allow(dead_code)]
 #![feature(core)]
-#![feature(io)]
 #![feature(collections)]
 
-use std::old_io::{Writer, IoResult};
-use std::fmt::Debug;
+use std::io::{self, Read, Write};
 use std::vec::Vec;
-use std::collections::HashMap;
-use std::num::Int;
+
+use defs::IdGenerator;
 
 pub use shapes::{Circle, Rect, RoundedRect, Ellipse, Line, PolyLine, Polygon};
-pub use common::{rgb, rgba};
+pub use attribs::{Attributes, AttribError, parse_attribs, parse_style};
+pub use clip::{ClipPath, Mask};
+pub use color::{Color, ColorError, Hsl, Hsv, LinearRgb, Oklab, NAMED_COLORS};
+pub use common::{rgb, rgba, Precision, NumberFormat, format_number};
+pub use container::Container;
+pub use defs::{Defs, DefsError, Handle, Units};
+pub use document::{Document, Element, Node};
+pub use entity::{Entity, Group};
+pub use filter::{Filter, FilterError, Primitive, Input, CompositeOperator, BlendMode,
+                 MorphologyOperator, Channel, ColorMatrix, TransferFunction, LightSource};
+pub use foreign::ForeignObject;
+pub use gradient::{LinearGradient, RadialGradient, Stop, SpreadMethod};
+pub use image::{Image, ImageError, ImageFormat};
+pub use length::{Length, LengthUnit, LengthError, DEFAULT_DPI};
+pub use marker::{Marker, Markers, MarkerUnits, Orient, BuiltinMarker};
+pub use palette::{Interpolation, ColorScale, interpolate, sequential, diverging, qualitative};
+pub use parser::{ParseError, parse_str, parse_reader, parse_fragment};
+pub use path::{Path, Data, Command, Position, PathError};
+pub use pattern::Pattern;
+pub use stream::StreamWriter;
+pub use style::{Style, StyleError, Paint, StrokeWidth, LineCap, LineJoin, MiterLimit,
+                DashArray, DashOffset, Opacity, FillRule, Visibility, FontFamily,
+                FontSize, FontWeight, FontStyle};
+pub use symbol::{Symbol, Use};
 pub use text::Text;
-pub use transform::Transform;
+pub use transform::{Transform, Matrix, Operation};
+pub use viewport::{Viewport, ViewportError, ViewBox, PreserveAspectRatio, Align, MeetOrSlice};
+pub use xml::{Serializer, Format, escape_text, escape_attrib};
 
 mod shapes;
 mod text;
 mod transform;
+mod clip;
 mod common;
+mod container;
+mod color;
+mod attribs;
+mod style;
+mod defs;
+mod document;
+mod entity;
+mod filter;
+mod foreign;
+mod gradient;
+mod image;
+mod length;
+mod marker;
+mod palette;
+mod parser;
+mod path;
+mod pattern;
+mod stream;
+mod symbol;
+mod viewport;
+mod xml;
 
 static DOC_TYPE: &'static str = "<!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \
-\"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">\n";
-static XMLNS: &'static str = "version=\"1.1\" xmlns=\"http://www.w3.org/2000/svg\" \
-xmlns:xlink=\"http://www.w3.org/1999/xlink\">\n";
-static STANDALONE_YES: &'static str = "<?xml version=\"1.0\" standalone=\"yes\"?>\n";
-static STANDALONE_NO: &'static str = "<?xml version=\"1.0\" standalone=\"no\"?>\n";
+\"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">";
+static SVG_NS: &'static str = "http://www.w3.org/2000/svg";
+static XLINK_NS: &'static str = "http://www.w3.org/1999/xlink";
+static STANDALONE_YES: &'static str = "<?xml version=\"1.0\" standalone=\"yes\"?>";
+static STANDALONE_NO: &'static str = "<?xml version=\"1.0\" standalone=\"no\"?>";
 
 pub trait SVGEntity {
-    fn gen_output(&self) -> String;
+    fn to_element(&self) -> Element;
+
+    fn gen_output(&self) -> String {
+        format!("{}", self.to_element())
+    }
+}
+
+// Attributes of the root element: its viewport, `extra` attributes and the
+// namespace declarations.
+fn root_attribs(viewport: &Viewport, extra: &Attributes) -> Attributes {
+    let mut attribs = Attributes::new();
+    viewport.insert_attribs(&mut attribs);
+    attribs.extend(extra);
+    attribs.insert("version", "1.1");
+    attribs.insert("xmlns", SVG_NS);
+    attribs.insert("xmlns:xlink", XLINK_NS);
+    attribs
+}
+
+// The xml declaration, the doctype and the start tag of the root element.
+fn prolog(standalone: bool,
+          attribs: &Attributes,
+          format: Format,
+          numbers: NumberFormat) -> String {
+    let mut s = Serializer::with_format(format, 0);
+    s.number_format(numbers);
+    s.raw(if standalone { STANDALONE_YES } else { STANDALONE_NO });
+    s.newline();
+    s.raw(DOC_TYPE);
+    s.newline();
+    s.start_tag("svg", attribs, false);
+    s.newline();
+    s.into_string()
+}
+
+// The end tag of the root element.
+fn epilog(format: Format) -> String {
+    let mut s = Serializer::with_format(format, 0);
+    s.end_tag("svg");
+    s.newline();
+    s.into_string()
+}
+
+fn text_element(name: &str, text: &str) -> Element {
+    let mut e = Element::new(name);
+    e.add_text(text);
+    e
+}
+
+// The kind of an open container, which only the matching `*_end` method
+// closes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ContainerKind {
+    Group,
+    Link,
+    Switch,
+    Case,
+    Svg,
+    // opened with `begin`
+    Element
 }
 
 struct Head {
     pub standalone: bool,
-    pub width: i32,
-    pub height: i32,
-    pub view_box: Option<(i32, i32, i32, i32)>,
+    pub viewport: Viewport,
+    // resolution used to convert absolute units to pixels
+    pub dpi: f64,
+    pub format: Format,
+    pub numbers: NumberFormat,
     pub desc: Option<String>,
-    pub title: Option<String>
+    pub title: Option<String>,
+    // extra attributes of the root element, e.g. from a parsed file
+    pub attribs: Attributes
 }
 
 impl Head {
-    pub fn new(width: i32, height: i32) -> Head {
+    pub fn new(viewport: Viewport) -> Head {
         Head {
             standalone: false,
-            width: width,
-            height: height,
-            view_box: None,
+            viewport: viewport,
+            dpi: DEFAULT_DPI,
+            format: Format::Plain,
+            numbers: NumberFormat::new(),
             desc: None,
-            title: None
+            title: None,
+            attribs: Attributes::new()
         }
     }
 }
 
-pub struct SVG<'a> {
+pub struct SVG {
     head: Head,
-    content: String
+    defs: Defs,
+    document: Document,
+    // containers (groups, links, ...) opened and not closed yet, innermost last
+    groups: Vec<(ContainerKind, Element)>,
+    ids: IdGenerator
 }
 
-fn make_attribs(attribs: &str) -> HashMap<String, String>{
-    let mut h = HashMap::new();
-    for s in attribs.split(' ') {
-        let t: Vec<&str> = s.split('=').collect();
-        h.insert(t[0us].to_string(), t[1us].to_string());
+fn text_of(element: &Element) -> String {
+    let mut text = String::new();
+    for child in element.children.iter() {
+        if let Node::Text(ref t) = *child { text.push_str(t.as_slice()) }
     }
-    h
+    text
 }
 
-impl<'a> SVG<'a> {
-    pub fn new(width: i32, height: i32) -> SVG<'a> {
+impl SVG {
+    pub fn new(width: Length, height: Length) -> SVG {
+        SVG::with_viewport(Viewport::sized(width, height))
+    }
+
+    /// An image with a fully configured root viewport, e.g. without width and
+    /// height so it scales to the page embedding it.
+    pub fn with_viewport(viewport: Viewport) -> SVG {
         SVG {
-            head: Head::new(width, height),
-            content: String::new()
+            head: Head::new(viewport),
+            defs: Defs::new(),
+            document: Document::new(),
+            groups: Vec::new(),
+            ids: IdGenerator::new()
+        }
+    }
+
+    /// Load an existing svg document. The root attributes, the title and the
+    /// description go to the head of the image, the content of the top level
+    /// `<defs>` elements to the definitions, everything else is kept in the
+    /// document so new entities can be added on top of it.
+    pub fn parse(input: &str) -> Result<SVG, ParseError> {
+        Ok(SVG::from_root(try!(parse_str(input))))
+    }
+
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<SVG, ParseError> {
+        Ok(SVG::from_root(try!(parse_reader(reader))))
+    }
+
+    fn from_root(root: Element) -> SVG {
+        let mut image = SVG::with_viewport(Viewport::new());
+        for &(ref at, ref value) in root.attribs.iter() {
+            let viewport = &mut image.head.viewport;
+            let known = match at.as_slice() {
+                "x"         => value.parse::<Length>().map(|x| viewport.x = Some(x)).is_ok(),
+                "y"         => value.parse::<Length>().map(|y| viewport.y = Some(y)).is_ok(),
+                "width"     => value.parse::<Length>().map(|w| viewport.width = Some(w)).is_ok(),
+                "height"    => value.parse::<Length>().map(|h| viewport.height = Some(h)).is_ok(),
+                "viewBox"   => {
+                    viewport.view_box = ViewBox::parse(value.as_slice());
+                    viewport.view_box.is_some()
+                },
+                "preserveAspectRatio" => {
+                    viewport.preserve_aspect_ratio = PreserveAspectRatio::parse(value.as_slice());
+                    viewport.preserve_aspect_ratio.is_some()
+                },
+                "version" | "xmlns" | "xmlns:xlink" => true,
+                _           => false
+            };
+            if !known { image.head.attribs.insert(at.as_slice(), value); }
         }
+        for child in root.children.into_iter() {
+            match child {
+                Node::Element(ref e) if e.name.as_slice() == "title" && image.head.title.is_none() => {
+                    image.head.title = Some(text_of(e))
+                },
+                Node::Element(ref e) if e.name.as_slice() == "desc" && image.head.desc.is_none() => {
+                    image.head.desc = Some(text_of(e))
+                },
+                Node::Element(ref e) if e.name.as_slice() == "defs" && e.attribs.is_empty() => {
+                    for d in e.children.iter() {
+                        if let Node::Element(ref d) = *d { image.defs.children.push(d.clone()) }
+                    }
+                },
+                Node::Element(e)    => image.document.add(e),
+                Node::Text(_)       => {/* whitespace between elements */}
+            }
+        }
+        image
     }
 
     pub fn standalone(&mut self, standalone: bool) {
         self.head.standalone = standalone;
     }
 
+    /// Layout of the output, one element per line by default. The minified
+    /// format also writes the numbers without their leading zero.
+    pub fn format(&mut self, format: Format) {
+        self.head.format = format;
+    }
+
+    /// Rounding of the numbers of the output, numbers are written with every
+    /// digit by default. The document keeps them unrounded, whenever they
+    /// were added or loaded.
+    pub fn precision(&mut self, precision: Precision) {
+        self.head.numbers.precision = precision;
+    }
+
+    /// Write the fractional numbers of the output without their leading zero,
+    /// e.g. `.5`.
+    pub fn short_numbers(&mut self, short: bool) {
+        self.head.numbers.short = short;
+    }
+
+    // the format of the numbers of the output
+    fn number_format(&self) -> NumberFormat {
+        let mut numbers = self.head.numbers;
+        numbers.short = numbers.short || self.head.format == Format::Minified;
+        numbers
+    }
+
+    /// Resolution used to convert absolute units to pixels, 96 by default.
+    pub fn dpi(&mut self, dpi: f64) {
+        self.head.dpi = dpi;
+    }
+
+    /// Size of the image in pixels at the configured resolution, `None` when
+    /// it is unknown or given in percents. Font relative units assume a 16px
+    /// font.
+    pub fn size_px(&self) -> Option<(f64, f64)> {
+        let dpi = self.head.dpi;
+        let px = |l: Option<Length>| l.and_then(|l| l.to_px(dpi, 16.));
+        match (px(self.head.viewport.width), px(self.head.viewport.height)) {
+            (Some(w), Some(h)) => Some((w, h)),
+            _                  => None
+        }
+    }
+
+    /// Set the width and height of the image, `None` leaves it to the page
+    /// embedding the image.
+    pub fn size(&mut self, width: Option<Length>, height: Option<Length>) {
+        self.head.viewport.width = width;
+        self.head.viewport.height = height;
+    }
+
+    /// Set the view box of the image, its width and height must be positive.
     pub fn view_box(&mut self,
-                    orig_x: i32,
-                    orig_y: i32,
-                    width: i32,
-                    height: i32) {
-        self.head.view_box = Some((orig_x, orig_y, width, height));
+                    orig_x: f64,
+                    orig_y: f64,
+                    width: f64,
+                    height: f64) -> Result<(), ViewportError> {
+        self.head.viewport.view_box = Some(try!(ViewBox::new(orig_x, orig_y, width, height)));
+        Ok(())
+    }
+
+    pub fn preserve_aspect_ratio(&mut self, aspect: PreserveAspectRatio) {
+        self.head.viewport.preserve_aspect_ratio = Some(aspect);
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.head.viewport
+    }
+
+    pub fn viewport_mut(&mut self) -> &mut Viewport {
+        &mut self.head.viewport
+    }
+
+    /// Convert a point in user coordinates to pixels of the image, `None` when
+    /// the size of the image in pixels is unknown.
+    pub fn user_to_device(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        self.head.viewport.user_to_viewport(self.head.dpi, 16.).map(|m| m.apply(x, y))
+    }
+
+    /// Convert a point in pixels of the image to user coordinates.
+    pub fn device_to_user(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        self.head.viewport.viewport_to_user(self.head.dpi, 16.).map(|m| m.apply(x, y))
     }
 
     pub fn desc(&mut self, text: &str) {
@@ -119,148 +370,116 @@ impl<'a> SVG<'a> {
         self.head.title = Some(text.to_string())
     }
 
-    pub fn add<T: SVGEntity>(&mut self, new_entity: &T) {
-        self.content.push_str(new_entity.gen_output().as_slice());
-    }
-
-    pub fn circle(&mut self,
-                  x: i32,
-                  y: i32,
-                  radius: u32,
-                  attribs: &str) {
-        self.content.push_str(Circle {
-            x: x,
-            y: y,
-            radius: radius,
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
-    }
-
-    pub fn rect(&mut self,
-                x: i32,
-                y: i32,
-                width: i32,
-                height: i32,
-                attribs: &str) {
-        self.content.push_str(Rect {
-            x: x,
-            y: y,
-            width: width,
-            height: height,
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
-    }
-
-    pub fn rounded_rect(&mut self,
-                        x: i32,
-                        y: i32,
-                        width: i32,
-                        height: i32,
-                        x_round: u32,
-                        y_round: u32,
-                        attribs: &str) {
-        self.content.push_str(RoundedRect {
-            x: x,
-            y: y,
-            width: width,
-            height: height,
-            x_round: x_round,
-            y_round: y_round,
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
-    }
-
-    pub fn ellipse(&mut self,
-                   x: i32,
-                   y: i32,
-                   x_radius: u32,
-                   y_radius: u32,
-                   attribs: &str) {
-        self.content.push_str(Ellipse {
-            x: x,
-            y: y,
-            x_radius: x_radius,
-            y_radius: y_radius,
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
-    }
-
-    pub fn line(&mut self,
-                x1: i32,
-                y1: i32,
-                x2: i32,
-                y2: i32,
-                attribs: &str) {
-        self.content.push_str(Line {
-            x1: x1,
-            y1: y1,
-            x2: x2,
-            y2: y2,
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
-    }
-
-    pub fn polyline<T: Int + Debug + Clone>(&mut self,
-                                           points: &Vec<(T, T)>,
-                                           attribs: &str) {
-        self.content.push_str(PolyLine {
-            points: points.clone(),
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
-    }
-
-    pub fn polygon<T: Int + Debug + Clone>(&mut self,
-                                          points: &Vec<(T, T)>,
-                                          attribs: &str) {
-        self.content.push_str(Polygon {
-            points: points.clone(),
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
-    }
-
-    pub fn text(&mut self,
-                x: i32,
-                y: i32,
-                text: &str,
-                attribs: &str) {
-        self.content.push_str(Text {
-            x: x,
-            y: y,
-            text: text.to_string(),
-            attribs: make_attribs(attribs),
-            transform: None
-        }.gen_output().as_slice())
+    /// Register a definition, e.g. a gradient, and return the handle used to
+    /// reference it. An id not used anywhere in the image is generated when
+    /// the definition has none, an id which is already used is an error.
+    pub fn define<T: SVGEntity>(&mut self, definition: &T) -> Result<Handle, DefsError> {
+        let mut element = definition.to_element();
+        match element.id().map(|id| id.to_string()) {
+            Some(ref id) if self.uses_id(id.as_slice()) => {
+                return Err(DefsError { desc: format!("duplicate id `{}`", id) })
+            },
+            Some(_)                                     => {/* a free id */},
+            None                                        => {
+                let id = self.generate_id(element.name.as_slice());
+                element.set_attrib("id", id);
+            }
+        }
+        self.defs.add(element)
+    }
+
+    /// The handle of one of the built-in markers in `color`, it is defined the
+    /// first time it is requested and reused afterwards.
+    pub fn builtin_marker(&mut self, marker: BuiltinMarker, color: &Color) -> Handle {
+        let id = marker.id(color);
+        if self.defs.find(id.as_slice()).is_none() {
+            let mut e = marker.marker(color).to_element();
+            e.set_attrib("id", id.as_slice());
+            // no definition has this id, add cannot fail
+            self.defs.add(e).unwrap();
+        }
+        Handle::new(id.as_slice())
+    }
+
+    // whether an element of the document, of an open container or of the
+    // definitions has the id `id`
+    fn uses_id(&self, id: &str) -> bool {
+        self.find(id).is_some() || self.defs.find(id).is_some()
+    }
+
+    // an id starting with `prefix` not used anywhere in the image
+    fn generate_id(&mut self, prefix: &str) -> String {
+        loop {
+            let id = self.ids.next(prefix);
+            if !self.uses_id(id.as_slice()) { return id }
+        }
+    }
+
+    pub fn defs(&self) -> &Defs {
+        &self.defs
+    }
+
+    pub fn defs_mut(&mut self) -> &mut Defs {
+        &mut self.defs
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Mutable access to the elements added so far. Groups which are still
+    /// open are only attached to the document once closed by `g_end`.
+    pub fn document_mut(&mut self) -> &mut Document {
+        &mut self.document
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Element> {
+        match self.document.find(id) {
+            Some(e) => Some(e),
+            None    => self.groups.iter().filter_map(|&(_, ref g)| g.find(id)).next()
+        }
+    }
+
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut Element> {
+        match self.document.find_mut(id) {
+            Some(e) => return Some(e),
+            None    => {/* look in the open groups */}
+        }
+        for &mut (_, ref mut g) in self.groups.iter_mut() {
+            if let Some(e) = g.find_mut(id) { return Some(e) }
+        }
+        None
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Element> {
+        match self.document.remove(id) {
+            Some(e) => return Some(e),
+            None    => {/* look in the open groups */}
+        }
+        for &mut (_, ref mut g) in self.groups.iter_mut() {
+            if let Some(e) = g.remove(id) { return Some(e) }
+        }
+        None
     }
 
     pub fn g_begin(&mut self,
                    id: Option<&str>,
                    transform: Option<&Transform>,
-                   attribs: Option<&HashMap<String, String>>) {
-        self.content.push_str("<g ");
+                   attribs: Option<&Attributes>) {
+        let mut g = Element::new("g");
         match id {
-            Some(i) => self.content.push_str(format!("id=\"{:?}\" ", i).as_slice()),
+            Some(i) => g.set_attrib("id", i),
             None    => {/* nothing to do */}
         }
         match transform {
-            Some(t) => self.content.push_str(format!("{:?} ", t.get()).as_slice()),
-            None    => {/* nothing to do */}
+            Some(t) if !t.is_empty() => g.set_attrib("transform", t.value()),
+            _                        => {/* nothing to do */}
         }
         match attribs {
-            Some(a) => {
-                for (at, value) in a.iter() {
-                    self.content.push_str(format!("{:?}=\"{:?}\" ", *at, *value).as_slice())
-                }
-            },
+            Some(a) => g.attribs.extend(a),
             None    => {/* nothing to do */}
         }
-        self.content.push_str(">\n");
+        self.begin_kind(ContainerKind::Group, g)
     }
 
     pub fn g_id(&mut self, id: &str) {
@@ -271,69 +490,196 @@ impl<'a> SVG<'a> {
         self.g_begin(None, Some(transform), None)
     }
 
-    pub fn g_attribs(&mut self, attribs: &HashMap<String, String>) {
+    pub fn g_attribs(&mut self, attribs: &Attributes) {
         self.g_begin(None, None, Some(attribs))
     }
 
-    pub fn g_translate(&mut self, x: i32, y: i32) {
+    /// Open a group clipped by the `ClipPath` registered as `clip_path`.
+    pub fn g_clip(&mut self, clip_path: &Handle) {
+        let mut attribs = Attributes::new();
+        attribs.insert("clip-path", clip_path.url());
+        self.g_begin(None, None, Some(&attribs))
+    }
+
+    /// Open a group masked by the `Mask` registered as `mask`.
+    pub fn g_mask(&mut self, mask: &Handle) {
+        let mut attribs = Attributes::new();
+        attribs.insert("mask", mask.url());
+        self.g_begin(None, None, Some(&attribs))
+    }
+
+    /// Open a group to which the `Filter` registered as `filter` is applied.
+    pub fn g_filter(&mut self, filter: &Handle) {
+        let mut attribs = Attributes::new();
+        attribs.insert("filter", filter.url());
+        self.g_begin(None, None, Some(&attribs))
+    }
+
+    pub fn g_translate(&mut self, x: f64, y: f64) {
         let mut t = Transform::new();
         t.translate(x, y);
         self.g_begin(None, Some(&t), None)
     }
 
-    pub fn g_rotate(&mut self, angle: i32) {
+    pub fn g_rotate(&mut self, angle: f64) {
         let mut t = Transform::new();
         t.rotate(angle);
         self.g_begin(None, Some(&t), None)
     }
 
-    pub fn g_scale(&mut self, x_scale: i32, y_scale: i32) {
+    pub fn g_scale(&mut self, x_scale: f64, y_scale: f64) {
         let mut t = Transform::new();
         t.scale(x_scale, y_scale);
         self.g_begin(None, Some(&t), None)
     }
 
     // FIXME: test if a skew of 0 for y or x don't break
-    pub fn g_skew(&mut self, x_factor: i32, y_factor: i32) {
+    pub fn g_skew(&mut self, x_factor: f64, y_factor: f64) {
         let mut t = Transform::new();
         t.skew_x(x_factor);
         t.skew_y(y_factor);
         self.g_begin(None, Some(&t), None)
     }
 
+    /// Close the innermost container if it is a group opened by `g_begin` or
+    /// one of its shortcuts. Like the other `*_end` methods, nothing happens
+    /// when the innermost container is of another kind.
     pub fn g_end(&mut self) {
-        self.content.push_str("</g>\n");
+        self.end_kind(ContainerKind::Group)
     }
 
-    pub fn finalize(&mut self, output: &'a mut Writer) -> IoResult<()>{
-        let mut o = String::new();
-        // Head
-        match self.head.standalone {
-            true    => o.push_str(STANDALONE_YES),
-            false   => o.push_str(STANDALONE_NO)
+    /// Open a link to `href`, every entity added until `a_end` is clickable.
+    pub fn a_begin(&mut self, href: &str, target: Option<&str>) {
+        let mut a = Element::new("a");
+        a.set_attrib("xlink:href", href);
+        match target {
+            Some(t) => a.set_attrib("target", t),
+            None    => {/* nothing to do */}
+        }
+        self.begin_kind(ContainerKind::Link, a)
+    }
+
+    pub fn a_end(&mut self) {
+        self.end_kind(ContainerKind::Link)
+    }
+
+    /// Open a `<switch>`, only its first child whose conditions are met is
+    /// rendered. The alternatives are opened with `case_begin`.
+    pub fn switch_begin(&mut self) {
+        self.begin_kind(ContainerKind::Switch, Element::new("switch"))
+    }
+
+    pub fn switch_end(&mut self) {
+        self.end_kind(ContainerKind::Switch)
+    }
+
+    /// Open an alternative of a `<switch>`, for the comma separated language
+    /// tags `languages` (`systemLanguage`) and the space separated feature
+    /// strings `features` (`requiredFeatures`). An alternative without
+    /// conditions is always rendered, it should be the last one.
+    pub fn case_begin(&mut self, languages: Option<&str>, features: Option<&str>) {
+        let mut g = Element::new("g");
+        match languages {
+            Some(l) => g.set_attrib("systemLanguage", l),
+            None    => {/* nothing to do */}
+        }
+        match features {
+            Some(f) => g.set_attrib("requiredFeatures", f),
+            None    => {/* nothing to do */}
+        }
+        self.begin_kind(ContainerKind::Case, g)
+    }
+
+    pub fn case_end(&mut self) {
+        self.end_kind(ContainerKind::Case)
+    }
+
+    /// Open a nested `<svg>` element, with its own coordinate system when the
+    /// viewport has a view box.
+    pub fn svg_begin(&mut self, viewport: &Viewport) {
+        let mut svg = Element::new("svg");
+        viewport.insert_attribs(&mut svg.attribs);
+        self.begin_kind(ContainerKind::Svg, svg)
+    }
+
+    pub fn svg_end(&mut self) {
+        self.end_kind(ContainerKind::Svg)
+    }
+
+    /// Open `element` as a container, the entities added until the matching
+    /// `end` become its children.
+    pub fn begin(&mut self, element: Element) {
+        self.begin_kind(ContainerKind::Element, element)
+    }
+
+    fn begin_kind(&mut self, kind: ContainerKind, element: Element) {
+        self.groups.push((kind, element))
+    }
+
+    /// Close the innermost container, whatever its kind. Nothing happens when
+    /// no container is open.
+    pub fn end(&mut self) {
+        match self.groups.pop() {
+            Some((_, g)) => self.add_element(g),
+            None         => {/* no container to close */}
+        }
+    }
+
+    // Close the innermost container if it is of the given kind, e.g. `g_end`
+    // neither closes a link nor an alternative of a switch. Nothing happens
+    // otherwise, as for `end` without open container.
+    fn end_kind(&mut self, kind: ContainerKind) {
+        let open = match self.groups.last() {
+            Some(&(k, _)) => k == kind,
+            None          => false
         };
-        o.push_str(DOC_TYPE);
-        o.push_str(format!("<svg width=\"{:?}cm\" height=\"{:?}cm\" ",
-                           self.head.width, self.head.height).as_slice());
-        match self.head.view_box {
-            Some((x, y, width, height)) => {
-                o.push_str(format!("viewBox=\"{:?} {:?} {:?} {:?}\" ", x, y, width, height).as_slice())
-            },
-            None                        => {/* nothing to do */}
+        if open { self.end() }
+    }
+
+    /// Close the open groups and write the image to `output`, one top level
+    /// element at a time so the whole file is never held in memory.
+    pub fn finalize<W: Write>(&mut self, output: &mut W) -> io::Result<()> {
+        while !self.groups.is_empty() {
+            self.end()
         }
-        o.push_str(XMLNS);
+        // Head
+        let format = self.head.format;
+        let numbers = self.number_format();
+        let attribs = root_attribs(&self.head.viewport, &self.head.attribs);
+        let head = prolog(self.head.standalone, &attribs, format, numbers);
+        try!(output.write_all(head.as_bytes()));
+        let mut s = Serializer::with_format(format, 1);
+        s.number_format(numbers);
         match self.head.title {
-            Some(ref t) => o.push_str(format!("<title>{:?}</title>\n", *t).as_slice()),
-            None    => {/* nothing to do */}
+            Some(ref t) => s.element(&text_element("title", t.as_slice())),
+            None        => {/* nothing to do */}
         }
         match self.head.desc {
-            Some(ref d) => o.push_str(format!("<desc>{:?}</desc>\n", *d).as_slice()),
-            None    => {/* nothing to do */}
+            Some(ref d) => s.element(&text_element("desc", d.as_slice())),
+            None        => {/* nothing to do */}
+        }
+        if !self.defs.is_empty() {
+            s.element(&self.defs.to_element())
         }
+        try!(output.write_all(s.as_slice().as_bytes()));
         // Body
-        o.push_str(self.content.clone().as_slice());
+        for child in self.document.children.iter() {
+            let mut s = Serializer::with_format(format, 1);
+            s.number_format(numbers);
+            s.node(child);
+            try!(output.write_all(s.as_slice().as_bytes()));
+        }
         // Close
-        o.push_str("</svg>\n");
-        output.write_str(o.as_slice())
+        output.write_all(epilog(format).as_bytes())
+    }
+}
+
+impl Container for SVG {
+    /// Add `element` to the innermost open group, or to the document.
+    fn add_element(&mut self, element: Element) {
+        match self.groups.last_mut() {
+            Some(&mut (_, ref mut g)) => g.add_child(element),
+            None                      => self.document.add(element)
+        }
     }
 }
@@ -0,0 +1,121 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Symbols, defined once and instanced with `<use>`.
+
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::{insert_attribs, insert_transform, number};
+use container::Container;
+use defs::Handle;
+use document::Element;
+use length::Length;
+use style::Style;
+use transform::Transform;
+use viewport::ViewBox;
+use SVGEntity;
+
+/// A `<symbol>`: content drawn in its own coordinate system and only rendered
+/// where it is referenced by a `Use`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Symbol {
+    pub view_box: Option<ViewBox>,
+    pub attribs: Attributes,
+    pub children: Vec<Element>
+}
+
+/// A `<use>` element, an instance of a symbol or of any element with an id.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Use {
+    pub href: Handle,
+    pub x: f64,
+    pub y: f64,
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub style: Style,
+    pub attribs: Attributes,
+    pub transform: Option<Transform>
+}
+
+impl Symbol {
+    pub fn new() -> Symbol {
+        Symbol {
+            view_box: None,
+            attribs: Attributes::new(),
+            children: Vec::new()
+        }
+    }
+
+    /// The area of the symbol content mapped to the size given by `Use`.
+    pub fn view_box(&mut self, view_box: ViewBox) -> &mut Symbol {
+        self.view_box = Some(view_box);
+        self
+    }
+}
+
+impl Use {
+    pub fn new(href: &Handle, x: f64, y: f64) -> Use {
+        Use {
+            href: href.clone(),
+            x: x,
+            y: y,
+            width: None,
+            height: None,
+            style: Style::new(),
+            attribs: Attributes::new(),
+            transform: None
+        }
+    }
+}
+
+impl Container for Symbol {
+    fn add_element(&mut self, element: Element) {
+        self.children.push(element)
+    }
+}
+
+impl SVGEntity for Symbol {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("symbol");
+        if let Some(v) = self.view_box { e.set_attrib("viewBox", v) }
+        insert_attribs(&mut e, &self.attribs);
+        for child in self.children.iter() {
+            e.add_child(child.clone());
+        }
+        e
+    }
+}
+
+impl SVGEntity for Use {
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("use");
+        e.set_attrib("xlink:href", self.href.href());
+        e.set_attrib("x", number(self.x));
+        e.set_attrib("y", number(self.y));
+        if let Some(w) = self.width { e.set_attrib("width", w) }
+        if let Some(h) = self.height { e.set_attrib("height", h) }
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e
+    }
+}
@@ -19,28 +19,33 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use std::collections::HashMap;
 
-use common::{insert_attribs, insert_transform};
+use attribs::Attributes;
+use common::{insert_attribs, insert_transform, number};
+use document::Element;
+use style::Style;
 use transform::Transform;
 use SVGEntity;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Text {
-    pub x: i32,
-    pub y: i32,
+    pub x: f64,
+    pub y: f64,
     pub text: String,
-    pub attribs: HashMap<String, String>,
+    pub style: Style,
+    pub attribs: Attributes,
     pub transform: Option<Transform>
 }
 
 impl SVGEntity for Text {
-    fn gen_output(&self) -> String {
-        let mut o = String::new();
-        o.push_str(format!("<text x=\"{:?}\" y=\"{:?}\"",
-                           self.x, self.y).as_slice());
-        o = insert_attribs(insert_transform(o, &self.transform), &self.attribs);
-        o.push_str(format!(" >{:?}</text>", self.text).as_slice());
-        o
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("text");
+        e.set_attrib("x", number(self.x));
+        e.set_attrib("y", number(self.y));
+        insert_transform(&mut e, &self.transform);
+        self.style.apply(&mut e);
+        insert_attribs(&mut e, &self.attribs);
+        e.add_text(self.text.as_slice());
+        e
     }
 }
@@ -19,66 +19,253 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
+use std::fmt;
+use std::num::Float;
+use std::ops::Mul;
+use std::vec::Vec;
+
+use common::number;
+
+/// A 2D affine transformation, i.e. the 3x3 matrix
+///
+/// ```text
+/// | a c e |
+/// | b d f |
+/// | 0 0 1 |
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64
+}
+
+/// One of the operations of a transform list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Matrix(Matrix),
+    Translate(f64, f64),
+    Scale(f64, f64),
+    /// angle in degrees, and optional center of the rotation
+    Rotate(f64, Option<(f64, f64)>),
+    SkewX(f64),
+    SkewY(f64)
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Transform {
-    output: String
+    operations: Vec<Operation>,
+    collapsed: bool
 }
 
-pub fn translate(x: i32, y: i32) -> String {
-    format!("translate({:?}, {:?})", x, y)
+impl Matrix {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Matrix {
+        Matrix { a: a, b: b, c: c, d: d, e: e, f: f }
+    }
+
+    pub fn identity() -> Matrix {
+        Matrix::new(1., 0., 0., 1., 0., 0.)
+    }
+
+    pub fn translate(x: f64, y: f64) -> Matrix {
+        Matrix::new(1., 0., 0., 1., x, y)
+    }
+
+    pub fn scale(x_scale: f64, y_scale: f64) -> Matrix {
+        Matrix::new(x_scale, 0., 0., y_scale, 0., 0.)
+    }
+
+    pub fn rotate(angle: f64) -> Matrix {
+        let (sin, cos) = (angle.to_radians().sin(), angle.to_radians().cos());
+        Matrix::new(cos, sin, -sin, cos, 0., 0.)
+    }
+
+    pub fn skew_x(angle: f64) -> Matrix {
+        Matrix::new(1., 0., angle.to_radians().tan(), 1., 0., 0.)
+    }
+
+    pub fn skew_y(angle: f64) -> Matrix {
+        Matrix::new(1., angle.to_radians().tan(), 0., 1., 0., 0.)
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// The inverse transformation, `None` if the matrix is not invertible.
+    pub fn inverse(&self) -> Option<Matrix> {
+        let det = self.determinant();
+        if det == 0. || !det.is_finite() { return None }
+        Some(Matrix::new(self.d / det,
+                         -self.b / det,
+                         -self.c / det,
+                         self.a / det,
+                         (self.c * self.f - self.d * self.e) / det,
+                         (self.b * self.e - self.a * self.f) / det))
+    }
+
+    /// Transform the point `(x, y)`.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.c * y + self.e,
+         self.b * x + self.d * y + self.f)
+    }
 }
 
-pub fn rotate(angle: i32) -> String {
-    format!("rotate({:?})", angle)
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    /// `self * other`, i.e. `other` is applied first.
+    fn mul(self, other: Matrix) -> Matrix {
+        Matrix::new(self.a * other.a + self.c * other.b,
+                    self.b * other.a + self.d * other.b,
+                    self.a * other.c + self.c * other.d,
+                    self.b * other.c + self.d * other.d,
+                    self.a * other.e + self.c * other.f + self.e,
+                    self.b * other.e + self.d * other.f + self.f)
+    }
 }
 
-pub fn skew_x(factor: i32) -> String {
-    format!("skewX({:?})", factor)
+impl Operation {
+    pub fn to_matrix(&self) -> Matrix {
+        match *self {
+            Operation::Matrix(m)                    => m,
+            Operation::Translate(x, y)              => Matrix::translate(x, y),
+            Operation::Scale(x, y)                  => Matrix::scale(x, y),
+            Operation::Rotate(angle, None)          => Matrix::rotate(angle),
+            Operation::Rotate(angle, Some((x, y)))  => {
+                Matrix::translate(x, y) * Matrix::rotate(angle) * Matrix::translate(-x, -y)
+            },
+            Operation::SkewX(angle)                 => Matrix::skew_x(angle),
+            Operation::SkewY(angle)                 => Matrix::skew_y(angle)
+        }
+    }
 }
 
-pub fn skew_y(factor: i32) -> String {
-    format!("skewY({:?})", factor)
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "matrix({} {} {} {} {} {})", number(self.a), number(self.b), number(self.c),
+               number(self.d), number(self.e), number(self.f))
+    }
 }
 
-pub fn scale(x_scale: i32, y_scale: i32) -> String {
-    format!("scale({:?}, {:?})", x_scale, y_scale)
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Operation::Matrix(ref m)                => write!(f, "{}", m),
+            Operation::Translate(x, y)              => {
+                write!(f, "translate({}, {})", number(x), number(y))
+            },
+            Operation::Scale(x, y)                  => write!(f, "scale({}, {})", number(x), number(y)),
+            Operation::Rotate(angle, None)          => write!(f, "rotate({})", number(angle)),
+            Operation::Rotate(angle, Some((x, y)))  => {
+                write!(f, "rotate({}, {}, {})", number(angle), number(x), number(y))
+            },
+            Operation::SkewX(angle)                 => write!(f, "skewX({})", number(angle)),
+            Operation::SkewY(angle)                 => write!(f, "skewY({})", number(angle))
+        }
+    }
 }
 
 impl Transform {
     pub fn new() -> Transform {
         Transform {
-            output: "transform=\"\"".to_string()
+            operations: Vec::new(),
+            collapsed: false
         }
     }
 
-    #[allow(unused_variables)]
-    fn insert(&mut self, tr: String) {
-        // let len = self.output.len();
-        // if self.output.char_at(len - 2) != '\"' { tr.insert_char(0, ' ') }
-        // self.output.insert(len - 1, tr)
+    fn insert(&mut self, operation: Operation) {
+        self.operations.push(operation)
+    }
+
+    pub fn translate(&mut self, x: f64, y: f64) {
+        self.insert(Operation::Translate(x, y))
+    }
+
+    pub fn rotate(&mut self, angle: f64) {
+        self.insert(Operation::Rotate(angle, None))
+    }
+
+    /// Rotation of `angle` degrees around the point `(cx, cy)`.
+    pub fn rotate_around(&mut self, angle: f64, cx: f64, cy: f64) {
+        self.insert(Operation::Rotate(angle, Some((cx, cy))))
+    }
+
+    pub fn skew_x(&mut self, angle: f64) {
+        self.insert(Operation::SkewX(angle))
+    }
+
+    pub fn skew_y(&mut self, angle: f64) {
+        self.insert(Operation::SkewY(angle))
     }
 
-    pub fn translate(&mut self, x: i32, y: i32) {
-        self.insert(translate(x, y))
+    pub fn scale(&mut self, x_scale: f64, y_scale: f64) {
+        self.insert(Operation::Scale(x_scale, y_scale))
     }
 
-    pub fn rotate(&mut self, angle: i32) {
-        self.insert(rotate(angle))
+    pub fn matrix(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        self.insert(Operation::Matrix(Matrix::new(a, b, c, d, e, f)))
     }
 
-    pub fn skew_x(&mut self, factor: i32) {
-        self.insert(skew_x(factor))
+    pub fn operations(&self) -> &Vec<Operation> {
+        &self.operations
     }
 
-    pub fn skew_y(&mut self, factor: i32) {
-        self.insert(skew_y(factor))
+    /// Whether the transform has no operation, i.e. is the identity. No
+    /// attribute is written for an empty transform.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
     }
 
-    pub fn scale(&mut self, x_scale: i32, y_scale: i32) {
-        self.insert(scale(x_scale, y_scale))
+    /// Append the operations of `other`, which then applies before this transform.
+    pub fn append(&mut self, other: &Transform) {
+        self.operations.push_all(other.operations.as_slice())
+    }
+
+    /// The product of the two transforms, `other` applying first.
+    pub fn multiply(&self, other: &Transform) -> Transform {
+        let mut t = self.clone();
+        t.append(other);
+        t
+    }
+
+    /// The whole transform list as a single matrix.
+    pub fn to_matrix(&self) -> Matrix {
+        self.operations.iter().fold(Matrix::identity(), |m, op| m * op.to_matrix())
+    }
+
+    pub fn inverse(&self) -> Option<Transform> {
+        self.to_matrix().inverse().map(|m| {
+            let mut t = Transform::new();
+            t.insert(Operation::Matrix(m));
+            t.collapsed = self.collapsed;
+            t
+        })
+    }
+
+    /// Transform the point `(x, y)`.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        self.to_matrix().apply(x, y)
+    }
+
+    /// Serialize as a single `matrix()` rather than as the list of operations.
+    pub fn collapse(&mut self, collapsed: bool) {
+        self.collapsed = collapsed
     }
 
     pub fn get(&self) -> String {
-        self.output.clone()
+        format!("transform=\"{}\"", self.value())
+    }
+
+    /// The transform list, without the `transform` attribute around it.
+    pub fn value(&self) -> String {
+        if self.collapsed {
+            return format!("{}", self.to_matrix())
+        }
+        let operations: Vec<String> = self.operations.iter().map(|op| format!("{}", op)).collect();
+        operations.connect(" ")
     }
 }
@@ -0,0 +1,337 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Viewport configuration: how a view box is fitted into a viewport, and the
+//! mapping between user and viewport coordinates.
+
+use std::error::Error;
+use std::fmt;
+use std::num::Float;
+use std::vec::Vec;
+
+use attribs::Attributes;
+use common::number;
+use length::Length;
+use transform::Matrix;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ViewportError {
+    pub desc: String
+}
+
+impl fmt::Display for ViewportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.desc)
+    }
+}
+
+impl Error for ViewportError {
+    fn description(&self) -> &str {
+        self.desc.as_slice()
+    }
+}
+
+/// Alignment part of `preserveAspectRatio`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Align {
+    /// scale non uniformly to fill the viewport
+    None,
+    XMinYMin,
+    XMidYMin,
+    XMaxYMin,
+    XMinYMid,
+    XMidYMid,
+    XMaxYMid,
+    XMinYMax,
+    XMidYMax,
+    XMaxYMax
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MeetOrSlice {
+    /// the whole view box is visible
+    Meet,
+    /// the viewport is entirely covered, the view box may be cut
+    Slice
+}
+
+/// Value of `preserveAspectRatio`, `xMidYMid meet` by default.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PreserveAspectRatio {
+    pub align: Align,
+    pub meet_or_slice: MeetOrSlice
+}
+
+/// The rectangle of user space mapped to the viewport.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ViewBox {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64
+}
+
+/// Position, size and coordinate system of an `<svg>` element. Everything is
+/// optional, e.g. an image without width and height scales to its container.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Viewport {
+    pub x: Option<Length>,
+    pub y: Option<Length>,
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub view_box: Option<ViewBox>,
+    pub preserve_aspect_ratio: Option<PreserveAspectRatio>
+}
+
+static ALIGNS: [(&'static str, Align); 10] = [
+    ("none", Align::None),
+    ("xMinYMin", Align::XMinYMin),
+    ("xMidYMin", Align::XMidYMin),
+    ("xMaxYMin", Align::XMaxYMin),
+    ("xMinYMid", Align::XMinYMid),
+    ("xMidYMid", Align::XMidYMid),
+    ("xMaxYMid", Align::XMaxYMid),
+    ("xMinYMax", Align::XMinYMax),
+    ("xMidYMax", Align::XMidYMax),
+    ("xMaxYMax", Align::XMaxYMax)
+];
+
+impl Align {
+    // position along x and y, 0 for min, 0.5 for mid and 1 for max
+    fn factors(&self) -> (f64, f64) {
+        match *self {
+            Align::None     => (0., 0.),
+            Align::XMinYMin => (0., 0.),
+            Align::XMidYMin => (0.5, 0.),
+            Align::XMaxYMin => (1., 0.),
+            Align::XMinYMid => (0., 0.5),
+            Align::XMidYMid => (0.5, 0.5),
+            Align::XMaxYMid => (1., 0.5),
+            Align::XMinYMax => (0., 1.),
+            Align::XMidYMax => (0.5, 1.),
+            Align::XMaxYMax => (1., 1.)
+        }
+    }
+}
+
+impl PreserveAspectRatio {
+    pub fn new(align: Align, meet_or_slice: MeetOrSlice) -> PreserveAspectRatio {
+        PreserveAspectRatio {
+            align: align,
+            meet_or_slice: meet_or_slice
+        }
+    }
+
+    /// `none`: the view box is stretched to the viewport.
+    pub fn none() -> PreserveAspectRatio {
+        PreserveAspectRatio::new(Align::None, MeetOrSlice::Meet)
+    }
+}
+
+impl PreserveAspectRatio {
+    /// Parse a `preserveAspectRatio` value such as `xMinYMid slice`.
+    pub fn parse(value: &str) -> Option<PreserveAspectRatio> {
+        let words: Vec<&str> = value.split(|c: char| c.is_whitespace())
+                                  .filter(|w| !w.is_empty())
+                                  .collect();
+        let align = match words.first() {
+            Some(w) => match ALIGNS.iter().find(|&&(name, _)| name == *w) {
+                Some(&(_, align)) => align,
+                None              => return None
+            },
+            None    => return None
+        };
+        let meet_or_slice = match words.len() {
+            1                         => MeetOrSlice::Meet,
+            2 if words[1] == "meet"   => MeetOrSlice::Meet,
+            2 if words[1] == "slice"  => MeetOrSlice::Slice,
+            _                         => return None
+        };
+        Some(PreserveAspectRatio::new(align, meet_or_slice))
+    }
+}
+
+impl ViewBox {
+    /// A view box at (`x`, `y`), whose width and height must be positive.
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Result<ViewBox, ViewportError> {
+        let view_box = ViewBox {
+            x: x,
+            y: y,
+            width: width,
+            height: height
+        };
+        if view_box.is_valid() {
+            Ok(view_box)
+        } else {
+            Err(ViewportError {
+                desc: format!("invalid view box {} {} {} {}", x, y, width, height)
+            })
+        }
+    }
+
+    // finite, with a positive width and height
+    fn is_valid(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+            && self.width > 0. && self.width.is_finite()
+            && self.height > 0. && self.height.is_finite()
+    }
+
+    /// Parse a `viewBox` value, four numbers separated by whitespace and/or
+    /// a comma. The width and height must be positive.
+    pub fn parse(value: &str) -> Option<ViewBox> {
+        let mut v = Vec::new();
+        for s in value.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()) {
+            match s.parse::<f64>() {
+                Ok(n)  => v.push(n),
+                Err(_) => return None
+            }
+        }
+        if v.len() == 4 {
+            ViewBox::new(v[0], v[1], v[2], v[3]).ok()
+        } else {
+            None
+        }
+    }
+
+    /// The matrix mapping this view box to a viewport of `width` by `height`
+    /// at (`x`, `y`), following `aspect`.
+    pub fn transform(&self,
+                     x: f64,
+                     y: f64,
+                     width: f64,
+                     height: f64,
+                     aspect: &PreserveAspectRatio) -> Matrix {
+        let mut sx = width / self.width;
+        let mut sy = height / self.height;
+        if aspect.align != Align::None {
+            let s = match aspect.meet_or_slice {
+                MeetOrSlice::Meet  => sx.min(sy),
+                MeetOrSlice::Slice => sx.max(sy)
+            };
+            sx = s;
+            sy = s;
+        }
+        let (ax, ay) = aspect.align.factors();
+        let tx = x - self.x * sx + (width - self.width * sx) * ax;
+        let ty = y - self.y * sy + (height - self.height * sy) * ay;
+        Matrix::new(sx, 0., 0., sy, tx, ty)
+    }
+}
+
+impl Viewport {
+    pub fn new() -> Viewport {
+        Default::default()
+    }
+
+    /// A viewport of `width` by `height`.
+    pub fn sized(width: Length, height: Length) -> Viewport {
+        Viewport {
+            width: Some(width),
+            height: Some(height),
+            ..Default::default()
+        }
+    }
+
+    /// Write the attributes of this viewport into `attribs`.
+    pub fn insert_attribs(&self, attribs: &mut Attributes) {
+        if let Some(x) = self.x { attribs.insert("x", x); }
+        if let Some(y) = self.y { attribs.insert("y", y); }
+        if let Some(w) = self.width { attribs.insert("width", w); }
+        if let Some(h) = self.height { attribs.insert("height", h); }
+        if let Some(v) = self.view_box { attribs.insert("viewBox", v); }
+        if let Some(p) = self.preserve_aspect_ratio { attribs.insert("preserveAspectRatio", p); }
+    }
+
+    /// The matrix mapping user coordinates to viewport pixels at `dpi`, with
+    /// `font_size` pixels for the font relative units. A missing width or
+    /// height is taken from the view box, `None` is returned when the size
+    /// cannot be resolved, e.g. for percentages, or when the view box is
+    /// empty.
+    pub fn user_to_viewport(&self, dpi: f64, font_size: f64) -> Option<Matrix> {
+        if let Some(ref v) = self.view_box {
+            if !v.is_valid() { return None }
+        }
+        let px = |l: Option<Length>, default: Option<f64>| match l {
+            Some(l) => l.to_px(dpi, font_size),
+            None    => default
+        };
+        let x = px(self.x, Some(0.)).unwrap_or(0.);
+        let y = px(self.y, Some(0.)).unwrap_or(0.);
+        let width = px(self.width, self.view_box.map(|v| v.width));
+        let height = px(self.height, self.view_box.map(|v| v.height));
+        match (self.view_box, width, height) {
+            (Some(v), Some(w), Some(h)) => {
+                let aspect = self.preserve_aspect_ratio.unwrap_or(Default::default());
+                Some(v.transform(x, y, w, h, &aspect))
+            },
+            (None, _, _)                => Some(Matrix::translate(x, y)),
+            _                           => None
+        }
+    }
+
+    /// The inverse of `user_to_viewport`.
+    pub fn viewport_to_user(&self, dpi: f64, font_size: f64) -> Option<Matrix> {
+        match self.user_to_viewport(dpi, font_size) {
+            Some(m) => m.inverse(),
+            None    => None
+        }
+    }
+}
+
+impl Default for PreserveAspectRatio {
+    fn default() -> PreserveAspectRatio {
+        PreserveAspectRatio::new(Align::XMidYMid, MeetOrSlice::Meet)
+    }
+}
+
+impl fmt::Display for Align {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            Align::None     => "none",
+            Align::XMinYMin => "xMinYMin",
+            Align::XMidYMin => "xMidYMin",
+            Align::XMaxYMin => "xMaxYMin",
+            Align::XMinYMid => "xMinYMid",
+            Align::XMidYMid => "xMidYMid",
+            Align::XMaxYMid => "xMaxYMid",
+            Align::XMinYMax => "xMinYMax",
+            Align::XMidYMax => "xMidYMax",
+            Align::XMaxYMax => "xMaxYMax"
+        })
+    }
+}
+
+impl fmt::Display for PreserveAspectRatio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.align, self.meet_or_slice) {
+            (Align::None, _)              => write!(f, "none"),
+            (align, MeetOrSlice::Meet)    => write!(f, "{}", align),
+            (align, MeetOrSlice::Slice)   => write!(f, "{} slice", align)
+        }
+    }
+}
+
+impl fmt::Display for ViewBox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {} {}",
+               number(self.x), number(self.y), number(self.width), number(self.height))
+    }
+}
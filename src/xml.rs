@@ -0,0 +1,370 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Serialization of the document model to well-formed XML.
+
+use std::vec::Vec;
+
+use attribs::{Attributes, parse_style};
+use common::{NumberFormat, format_number};
+use document::{Element, Node};
+
+/// Escape `text` for use as character data.
+pub fn escape_text(text: &str) -> String {
+    let mut o = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => o.push_str("&amp;"),
+            '<' => o.push_str("&lt;"),
+            '>' => o.push_str("&gt;"),
+            c   => o.push(c)
+        }
+    }
+    o
+}
+
+/// Escape `value` for use inside a double quoted attribute value. Whitespace
+/// characters other than space are written as character references so they
+/// survive attribute value normalization.
+pub fn escape_attrib(value: &str) -> String {
+    let mut o = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&'  => o.push_str("&amp;"),
+            '<'  => o.push_str("&lt;"),
+            '>'  => o.push_str("&gt;"),
+            '"'  => o.push_str("&quot;"),
+            '\t' => o.push_str("&#9;"),
+            '\n' => o.push_str("&#10;"),
+            '\r' => o.push_str("&#13;"),
+            c    => o.push(c)
+        }
+    }
+    o
+}
+
+/// Layout of the serialized markup.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    /// one element per line, without indentation
+    Plain,
+    /// nested elements indented by `indent` spaces, the attributes of a start
+    /// tag going past `wrap` columns are written one per line
+    Pretty { indent: usize, wrap: Option<usize> },
+    /// no whitespace between elements and the attributes having their default
+    /// value dropped
+    Minified
+}
+
+// attributes made of numbers, lengths or lists of them (coordinates, path
+// data, transforms...), whose numbers are rounded on output, along with the
+// properties of the same name in `style`
+static NUMERIC_ATTRIBUTES: [&'static str; 77] = [
+    "x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r", "rx", "ry", "fx", "fy", "fr", "dx",
+    "dy", "width", "height", "d", "points", "transform", "gradientTransform",
+    "patternTransform", "viewBox", "offset", "opacity", "fill-opacity", "stroke-opacity",
+    "stop-opacity", "flood-opacity", "stroke-width", "stroke-miterlimit",
+    "stroke-dasharray", "stroke-dashoffset", "font-size", "letter-spacing", "word-spacing",
+    "baseline-shift", "kerning", "rotate", "textLength", "startOffset", "refX", "refY",
+    "markerWidth", "markerHeight", "orient", "pathLength", "stdDeviation", "scale", "k1",
+    "k2", "k3", "k4", "radius", "values", "tableValues", "slope", "intercept", "amplitude",
+    "exponent", "baseFrequency", "azimuth", "elevation", "pointsAtX", "pointsAtY",
+    "pointsAtZ", "z", "specularExponent", "specularConstant", "diffuseConstant",
+    "surfaceScale", "limitingConeAngle", "kernelMatrix", "kernelUnitLength", "divisor",
+    "bias"
+];
+
+// attributes which can be left out when they have their initial value, the
+// presentation attributes are inherited so they are never dropped
+static DEFAULT_ATTRIBUTES: [(&'static str, &'static str, &'static str); 16] = [
+    ("circle", "cx", "0"), ("circle", "cy", "0"),
+    ("ellipse", "cx", "0"), ("ellipse", "cy", "0"),
+    ("line", "x1", "0"), ("line", "y1", "0"), ("line", "x2", "0"), ("line", "y2", "0"),
+    ("rect", "x", "0"), ("rect", "y", "0"),
+    ("image", "x", "0"), ("image", "y", "0"),
+    ("use", "x", "0"), ("use", "y", "0"),
+    ("text", "x", "0"), ("text", "y", "0")
+];
+
+// Round the numbers of `value` according to `format`, leaving everything else
+// as is. In path data (`path`), the flags of the arcs are single digits which
+// may be written without separator, they are copied untouched.
+fn format_numbers(value: &str, format: &NumberFormat, path: bool) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let digit = |i: usize| i < chars.len() && chars[i].is_digit(10);
+    let mut o = String::with_capacity(value.len());
+    // the last number written when nothing separates it from the next one
+    let mut previous: Option<String> = None;
+    // the current path command and the number of arguments read for it
+    let mut command = ' ';
+    let mut argument = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let sign = if chars[i] == '-' || chars[i] == '+' { 1 } else { 0 };
+        let starts_number = digit(i + sign)
+            || (i + sign < chars.len() && chars[i + sign] == '.' && digit(i + sign + 1));
+        if !starts_number {
+            if path && chars[i].is_alphabetic() {
+                command = chars[i];
+                argument = 0;
+            }
+            o.push(chars[i]);
+            previous = None;
+            i += 1;
+            continue
+        }
+        if (command == 'a' || command == 'A') && (argument % 7 == 3 || argument % 7 == 4) {
+            o.push(chars[i]);
+            previous = None;
+            argument += 1;
+            i += 1;
+            continue
+        }
+        let mut end = i + sign;
+        while digit(end) { end += 1 }
+        if end < chars.len() && chars[end] == '.' {
+            end += 1;
+            while digit(end) { end += 1 }
+        }
+        // an exponent, but not the `e` of `em`
+        if end < chars.len() && (chars[end] == 'e' || chars[end] == 'E') {
+            let mut exponent = end + 1;
+            if exponent < chars.len() && (chars[exponent] == '-' || chars[exponent] == '+') {
+                exponent += 1
+            }
+            if digit(exponent) {
+                end = exponent;
+                while digit(end) { end += 1 }
+            }
+        }
+        let token: String = chars[i..end].iter().cloned().collect();
+        let formatted = match token.as_slice().parse::<f64>() {
+            Ok(n)  => format_number(n, format),
+            Err(_) => token
+        };
+        // the sign or the point separating two numbers may have been rounded
+        // away, e.g. `1-0.001` or `1.5.5`
+        if let Some(ref p) = previous {
+            let separated = match formatted.chars().next() {
+                Some('-') => true,
+                Some('.') => p.contains_char('.') && !p.contains_char('e'),
+                _         => false
+            };
+            if !separated { o.push(' ') }
+        }
+        o.push_str(formatted.as_slice());
+        previous = Some(formatted);
+        argument += 1;
+        i = end;
+    }
+    o
+}
+
+// The value of the attribute `name` with its numbers formatted according to
+// `format`.
+fn format_attrib(name: &str, value: &str, format: &NumberFormat) -> String {
+    let numeric = |name: &str| NUMERIC_ATTRIBUTES.iter().any(|&a| a == name);
+    if name == "style" {
+        match parse_style(value) {
+            Ok(declarations) => {
+                let declarations: Vec<String> = declarations.iter().map(|&(ref p, ref v)| {
+                    if numeric(p.as_slice()) {
+                        format!("{}:{}", p, format_numbers(v.as_slice(), format, false))
+                    } else {
+                        format!("{}:{}", p, v)
+                    }
+                }).collect();
+                declarations.connect(";")
+            },
+            Err(_)           => value.to_string()
+        }
+    } else if numeric(name) {
+        format_numbers(value, format, name == "d")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes XML markup into a string, taking care of the escaping.
+pub struct Serializer {
+    output: String,
+    format: Format,
+    numbers: NumberFormat,
+    // nesting level of the element being written
+    depth: usize,
+    // inside an element with text content, where whitespace is significant
+    inline: bool
+}
+
+impl Serializer {
+    pub fn new() -> Serializer {
+        Serializer::with_format(Format::Plain, 0)
+    }
+
+    /// A serializer writing elements nested `depth` levels deep in `format`.
+    pub fn with_format(format: Format, depth: usize) -> Serializer {
+        Serializer {
+            output: String::new(),
+            format: format,
+            numbers: NumberFormat::new(),
+            depth: depth,
+            inline: false
+        }
+    }
+
+    /// Round the numbers of the attributes holding coordinates, sizes, path
+    /// data, transforms... according to `numbers`. They are written as they
+    /// are in the document by default.
+    pub fn number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
+    /// Write `s` as is, it must already be valid markup.
+    pub fn raw(&mut self, s: &str) {
+        self.output.push_str(s)
+    }
+
+    /// End the current line, unless the output is minified or whitespace is
+    /// significant.
+    pub fn newline(&mut self) {
+        if self.format != Format::Minified && !self.inline {
+            self.output.push('\n')
+        }
+    }
+
+    /// Indent the line for an element at the current depth, when pretty
+    /// printing.
+    pub fn indent(&mut self) {
+        let depth = self.depth;
+        self.indent_to(depth)
+    }
+
+    fn indent_to(&mut self, depth: usize) {
+        if let Format::Pretty { indent, .. } = self.format {
+            if !self.inline {
+                for _ in 0..depth * indent { self.output.push(' ') }
+            }
+        }
+    }
+
+    pub fn start_tag(&mut self, name: &str, attribs: &Attributes, empty: bool) {
+        let minified = self.format == Format::Minified;
+        let mut written: Vec<(&str, String)> = Vec::new();
+        for &(ref at, ref value) in attribs.iter() {
+            let at = at.as_slice();
+            let value = if self.numbers == NumberFormat::new() {
+                escape_attrib(value.as_slice())
+            } else {
+                escape_attrib(format_attrib(at, value.as_slice(), &self.numbers).as_slice())
+            };
+            if minified && DEFAULT_ATTRIBUTES.iter().any(|&(e, a, v)| e == name && a == at
+                                                                   && v == value.as_slice()) {
+                continue
+            }
+            written.push((at, value));
+        }
+        // attributes one per line when the tag would be too long
+        let wrap = match self.format {
+            Format::Pretty { indent, wrap: Some(column) } if !self.inline && written.len() > 1 => {
+                let length = written.iter().fold(self.depth * indent + name.len() + 3,
+                                                 |l, &(at, ref v)| l + at.len() + v.len() + 4);
+                length > column
+            },
+            _                                                                            => false
+        };
+        self.output.push('<');
+        self.output.push_str(name);
+        for &(at, ref value) in written.iter() {
+            if wrap {
+                self.output.push('\n');
+                let depth = self.depth + 1;
+                self.indent_to(depth);
+            } else {
+                self.output.push(' ');
+            }
+            self.output.push_str(at);
+            self.output.push_str("=\"");
+            self.output.push_str(value.as_slice());
+            self.output.push('"');
+        }
+        self.output.push_str(match (empty, minified) {
+            (true, true)   => "/>",
+            (true, false)  => " />",
+            (false, _)     => ">"
+        })
+    }
+
+    pub fn end_tag(&mut self, name: &str) {
+        self.output.push_str("</");
+        self.output.push_str(name);
+        self.output.push('>')
+    }
+
+    pub fn text(&mut self, text: &str) {
+        self.output.push_str(escape_text(text).as_slice())
+    }
+
+    pub fn node(&mut self, node: &Node) {
+        match *node {
+            Node::Element(ref e) => self.element(e),
+            Node::Text(ref t)    => self.text(t.as_slice())
+        }
+    }
+
+    /// Write `element` and its children, each element being followed by a new
+    /// line. Nothing is added inside elements holding text, such as `<text>`
+    /// with `<tspan>` children, as whitespace is significant there.
+    pub fn element(&mut self, element: &Element) {
+        let name = element.name.as_slice();
+        let depth = self.depth;
+        self.indent();
+        if element.children.is_empty() {
+            self.start_tag(name, &element.attribs, true);
+            self.newline();
+            return
+        }
+        let inline = element.children.iter().any(|n| match *n {
+            Node::Text(_)    => true,
+            Node::Element(_) => false
+        });
+        self.start_tag(name, &element.attribs, false);
+        let outer_inline = self.inline;
+        self.inline = outer_inline || inline;
+        self.newline();
+        self.depth = depth + 1;
+        for child in element.children.iter() {
+            self.node(child);
+        }
+        self.depth = depth;
+        if !inline { self.indent() }
+        self.end_tag(name);
+        self.inline = outer_inline;
+        self.newline();
+    }
+
+    pub fn as_slice(&self) -> &str {
+        self.output.as_slice()
+    }
+
+    pub fn into_string(self) -> String {
+        self.output
+    }
+}